@@ -0,0 +1,165 @@
+//! Hybrid asymmetric encryption via X25519 ECDH.
+//!
+//! Every other entry point in this crate is password-based, which
+//! requires both sides to already share a secret. This module adds a
+//! public-key alternative modeled on devolutions-crypto's
+//! `encrypt_asymmetric`/`generate_keypair`: the sender generates a
+//! one-shot ephemeral X25519 keypair, computes an ECDH shared secret
+//! against the recipient's long-term public key, and runs it through
+//! HKDF-SHA256 to derive an AES-256-GCM key. Only the recipient's
+//! long-term private key needs to survive between calls, so this is a
+//! "key-at-rest" path rather than a session handshake like the
+//! `handshake` module.
+//!
+//! The container written is the same versioned format as
+//! `container::encode_header`/`decode_header`, tagged with the
+//! `ALGORITHM_ID_X25519_AES_256_GCM` algorithm id and `KDF_ID_NONE`
+//! (there's no password to derive from, so the salt is empty and the
+//! persisted Argon2 parameters are unused), with the body extended to
+//! carry the ephemeral public key:
+//! `ephemeral_pubkey(32) || nonce(12) || ciphertext_len(u32 BE) || ciphertext`.
+
+#[cfg(feature = "std")]
+use aes_gcm::aead::{Aead, KeyInit};
+#[cfg(feature = "std")]
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+#[cfg(feature = "std")]
+use hkdf::Hkdf;
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::RngCore;
+#[cfg(feature = "std")]
+use sha2::Sha256;
+#[cfg(feature = "std")]
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+/// `algorithm_id` tag for this module's container, distinct from the
+/// password-based tags in `std_features`.
+pub(crate) const ALGORITHM_ID_X25519_AES_256_GCM: u8 = 2;
+/// `kdf_id` tag meaning "no KDF was used" -- there's no password here.
+pub(crate) const KDF_ID_NONE: u8 = 0xff;
+
+#[cfg(feature = "std")]
+/// Generates a fresh X25519 keypair for use as a recipient identity: the
+/// private half is the long-term secret passed to [`decrypt_asymmetric`],
+/// the public half is what senders pass to [`encrypt_asymmetric`].
+/// Returns `(public, private)`.
+pub fn generate_keypair() -> ([u8; 32], [u8; 32]) {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&secret);
+    (public.to_bytes(), secret.to_bytes())
+}
+
+#[cfg(feature = "std")]
+/// Encrypts `data` to `recipient_public`: generates an ephemeral X25519
+/// keypair, computes the ECDH shared secret, derives an AES-256-GCM key
+/// via HKDF-SHA256, and returns a versioned container carrying the
+/// ephemeral public key alongside the ciphertext.
+pub fn encrypt_asymmetric(data: &[u8], recipient_public: &[u8; 32]) -> Result<std::vec::Vec<u8>, &'static str> {
+    let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_public = PublicKey::from(&ephemeral_secret);
+    let recipient_public = PublicKey::from(*recipient_public);
+
+    let shared_secret = ephemeral_secret.diffie_hellman(&recipient_public);
+    let key = derive_key(shared_secret.as_bytes(), ephemeral_public.as_bytes(), recipient_public.as_bytes());
+
+    let mut nonce_bytes = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce_bytes);
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce_bytes), data).map_err(|_| "encryption failed")?;
+
+    let mut out = crate::container::encode_header(
+        ALGORITHM_ID_X25519_AES_256_GCM,
+        KDF_ID_NONE,
+        &[],
+        crate::kdf::Argon2Params::default_cost(),
+    );
+    out.extend_from_slice(ephemeral_public.as_bytes());
+    out.extend_from_slice(&nonce_bytes);
+    out.extend_from_slice(&(ciphertext.len() as u32).to_be_bytes());
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+#[cfg(feature = "std")]
+/// Reverses [`encrypt_asymmetric`] using the recipient's long-term
+/// private key.
+pub fn decrypt_asymmetric(data: &[u8], recipient_private: &[u8; 32]) -> Result<std::vec::Vec<u8>, &'static str> {
+    let header = crate::container::decode_header(data)?;
+    if header.algorithm_id != ALGORITHM_ID_X25519_AES_256_GCM {
+        return Err("not an asymmetric container");
+    }
+
+    let body = &data[header.body_offset..];
+    if body.len() < 32 + 16 {
+        return Err("buffer too short for an ephemeral public key, nonce, and length prefix");
+    }
+
+    let ephemeral_public = PublicKey::from(<[u8; 32]>::try_from(&body[0..32]).unwrap());
+    let nonce_bytes = &body[32..44];
+    let ciphertext_len = u32::from_be_bytes([body[44], body[45], body[46], body[47]]) as usize;
+    if body.len() < 48 + ciphertext_len {
+        return Err("buffer too short for the declared ciphertext length");
+    }
+    let ciphertext = &body[48..48 + ciphertext_len];
+
+    let recipient_secret = StaticSecret::from(*recipient_private);
+    let recipient_public = PublicKey::from(&recipient_secret);
+    let shared_secret = recipient_secret.diffie_hellman(&ephemeral_public);
+    let key = derive_key(shared_secret.as_bytes(), ephemeral_public.as_bytes(), recipient_public.as_bytes());
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    cipher.decrypt(Nonce::from_slice(nonce_bytes), ciphertext).map_err(|_| "authentication failed")
+}
+
+#[cfg(feature = "std")]
+/// Derives a 32-byte AES key from the ECDH shared secret via
+/// HKDF-SHA256, binding both public keys into the `info` parameter so the
+/// same shared secret can't be reinterpreted under a different recipient.
+fn derive_key(shared_secret: &[u8], ephemeral_public: &[u8], recipient_public: &[u8]) -> [u8; 32] {
+    let mut info = std::vec::Vec::with_capacity(ephemeral_public.len() + recipient_public.len());
+    info.extend_from_slice(ephemeral_public);
+    info.extend_from_slice(recipient_public);
+
+    let hk = Hkdf::<Sha256>::new(None, shared_secret);
+    let mut key = [0u8; 32];
+    hk.expand(&info, &mut key).expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let (public, private) = generate_keypair();
+        let plaintext = b"hybrid encryption round trip";
+
+        let container = encrypt_asymmetric(plaintext, &public).unwrap();
+        let decrypted = decrypt_asymmetric(&container, &private).unwrap();
+
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn decrypt_rejects_a_tampered_ciphertext() {
+        let (public, private) = generate_keypair();
+        let mut container = encrypt_asymmetric(b"hybrid encryption round trip", &public).unwrap();
+        let last = container.len() - 1;
+        container[last] ^= 0xff;
+
+        assert_eq!(decrypt_asymmetric(&container, &private), Err("authentication failed"));
+    }
+
+    #[test]
+    fn decrypt_rejects_the_wrong_private_key() {
+        let (public, _) = generate_keypair();
+        let (_, wrong_private) = generate_keypair();
+        let container = encrypt_asymmetric(b"hybrid encryption round trip", &public).unwrap();
+
+        assert_eq!(decrypt_asymmetric(&container, &wrong_private), Err("authentication failed"));
+    }
+}
@@ -0,0 +1,166 @@
+//! Versioned, self-describing ciphertext container.
+//!
+//! `encrypt_data` used to write only `nonce || ciphertext_len || ciphertext`,
+//! while `derive_key_from_password_internal` generated a *fresh random
+//! Argon2 salt on every call* and never stored it anywhere -- so
+//! `decrypt_data` derived a different key from the same password and could
+//! never reproduce the original one. This module fixes that by prepending
+//! a header that records everything needed to reconstruct the key:
+//!
+//! ```text
+//! magic (4 bytes) || format_version (u16, BE) || algorithm_id (u8)
+//!     || kdf_id (u8) || salt_len (u8) || salt (salt_len bytes)
+//! ```
+//!
+//! followed by the existing `nonce || ciphertext_len || ciphertext` region.
+//! The salt generated at encrypt time is written into the header; at
+//! decrypt time it's read back out and fed to the same KDF, so the key is
+//! reconstructed exactly. The header format is versioned so future changes
+//! (new algorithms, new KDF parameters) stay forward-compatible.
+//!
+//! Version 2 adds the Argon2 cost parameters (see
+//! [`crate::kdf::Argon2Params`]) right after the salt:
+//! `memory_kib(u32 BE) || iterations(u32 BE) || parallelism(u32 BE) ||
+//! variant(u8)`, so a container encrypted with non-default cost settings
+//! can still be decrypted without the caller guessing them. Version 1
+//! headers (no trailing parameters) are still read; they're assumed to
+//! have used `Argon2::default()`'s cost, which [`crate::kdf::Argon2Params::default_cost`]
+//! reproduces exactly.
+
+const MAGIC: [u8; 4] = *b"CRY1";
+const FORMAT_VERSION_V1: u16 = 1;
+const FORMAT_VERSION: u16 = 2;
+
+/// Parsed container header, plus where the nonce/ciphertext region starts.
+#[derive(Debug)]
+pub struct Header {
+    pub algorithm_id: u8,
+    pub kdf_id: u8,
+    pub salt: std::vec::Vec<u8>,
+    pub argon2_params: crate::kdf::Argon2Params,
+    pub body_offset: usize,
+}
+
+/// Serializes a container header with the given algorithm/KDF tags, salt,
+/// and Argon2 cost parameters.
+pub fn encode_header(
+    algorithm_id: u8,
+    kdf_id: u8,
+    salt: &[u8],
+    argon2_params: crate::kdf::Argon2Params,
+) -> std::vec::Vec<u8> {
+    let mut out = std::vec::Vec::with_capacity(4 + 2 + 1 + 1 + 1 + salt.len() + 13);
+    out.extend_from_slice(&MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+    out.push(algorithm_id);
+    out.push(kdf_id);
+    out.push(salt.len() as u8);
+    out.extend_from_slice(salt);
+    out.extend_from_slice(&argon2_params.memory_kib.to_be_bytes());
+    out.extend_from_slice(&argon2_params.iterations.to_be_bytes());
+    out.extend_from_slice(&argon2_params.parallelism.to_be_bytes());
+    out.push(argon2_params.variant);
+    out
+}
+
+/// Validates the magic/version and parses the header out of `data`,
+/// without touching the nonce/ciphertext region that follows it.
+pub fn decode_header(data: &[u8]) -> Result<Header, &'static str> {
+    if data.len() < 9 {
+        return Err("buffer too short to contain a container header");
+    }
+    if data[0..4] != MAGIC {
+        return Err("bad magic: not a CRUSTy-Core container");
+    }
+    let version = u16::from_be_bytes([data[4], data[5]]);
+    if version != FORMAT_VERSION && version != FORMAT_VERSION_V1 {
+        return Err("unsupported container format version");
+    }
+
+    let algorithm_id = data[6];
+    let kdf_id = data[7];
+    let salt_len = data[8] as usize;
+    let salt_start = 9;
+    let salt_end = salt_start + salt_len;
+    if data.len() < salt_end {
+        return Err("buffer too short to contain the declared salt");
+    }
+
+    let (argon2_params, body_offset) = if version == FORMAT_VERSION_V1 {
+        (crate::kdf::Argon2Params::default_cost(), salt_end)
+    } else {
+        let params_end = salt_end + 13;
+        if data.len() < params_end {
+            return Err("buffer too short to contain the declared Argon2 parameters");
+        }
+        let memory_kib = u32::from_be_bytes(data[salt_end..salt_end + 4].try_into().unwrap());
+        let iterations = u32::from_be_bytes(data[salt_end + 4..salt_end + 8].try_into().unwrap());
+        let parallelism = u32::from_be_bytes(data[salt_end + 8..salt_end + 12].try_into().unwrap());
+        let variant = data[salt_end + 12];
+        (crate::kdf::Argon2Params { memory_kib, iterations, parallelism, variant }, params_end)
+    };
+
+    Ok(Header {
+        algorithm_id,
+        kdf_id,
+        salt: data[salt_start..salt_end].to_vec(),
+        argon2_params,
+        body_offset,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_a_v2_header() {
+        let salt = [7u8; 16];
+        let params = crate::kdf::Argon2Params { memory_kib: 65536, iterations: 3, parallelism: 2, variant: 2 };
+        let encoded = encode_header(1, 0, &salt, params);
+
+        let header = decode_header(&encoded).unwrap();
+        assert_eq!(header.algorithm_id, 1);
+        assert_eq!(header.kdf_id, 0);
+        assert_eq!(header.salt, salt);
+        assert_eq!(header.argon2_params.memory_kib, params.memory_kib);
+        assert_eq!(header.argon2_params.iterations, params.iterations);
+        assert_eq!(header.argon2_params.parallelism, params.parallelism);
+        assert_eq!(header.argon2_params.variant, params.variant);
+        assert_eq!(header.body_offset, encoded.len());
+    }
+
+    #[test]
+    fn decodes_a_v1_header_with_default_cost() {
+        let mut data = std::vec::Vec::new();
+        data.extend_from_slice(&MAGIC);
+        data.extend_from_slice(&FORMAT_VERSION_V1.to_be_bytes());
+        data.push(1);
+        data.push(0);
+        data.push(4);
+        data.extend_from_slice(&[1, 2, 3, 4]);
+
+        let header = decode_header(&data).unwrap();
+        assert_eq!(header.salt, [1, 2, 3, 4]);
+        assert_eq!(header.body_offset, data.len());
+        let default_cost = crate::kdf::Argon2Params::default_cost();
+        assert_eq!(header.argon2_params.memory_kib, default_cost.memory_kib);
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let salt = [0u8; 8];
+        let mut encoded = encode_header(1, 0, &salt, crate::kdf::Argon2Params::default_cost());
+        encoded[0] = b'X';
+        assert_eq!(decode_header(&encoded).unwrap_err(), "bad magic: not a CRUSTy-Core container");
+    }
+
+    #[test]
+    fn rejects_unsupported_version() {
+        let mut data = std::vec::Vec::new();
+        data.extend_from_slice(&MAGIC);
+        data.extend_from_slice(&99u16.to_be_bytes());
+        data.extend_from_slice(&[0, 0, 0]);
+        assert_eq!(decode_header(&data).unwrap_err(), "unsupported container format version");
+    }
+}
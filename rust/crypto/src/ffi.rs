@@ -0,0 +1,258 @@
+//! Typed C++ interop layer built on the `cxx` crate.
+//!
+//! This is the replacement for the hand-written `extern "C"` declarations
+//! that `build.rs` used to string-concatenate into `crypto_interface.h`.
+//! The bridge below is the single source of truth for the C++-visible
+//! surface: `cxx_build` generates the header and call-site glue directly
+//! from these signatures, so the C++ side can no longer drift from the
+//! real Rust ABI the way the old hand-written header could.
+//!
+//! The plain `extern "C"` functions in [`crate`] (`encrypt_data`,
+//! `decrypt_data`, ...) are kept as-is for embedded targets, which don't
+//! link against libc++ and can't use `cxx`. This module is only compiled
+//! for `std` targets that build against the C++ bridge.
+
+#[cxx::bridge(namespace = "crusty::crypto")]
+pub mod ffi {
+    /// Mirrors [`crate::CryptoErrorCode`] for the `cxx` bridge, so a C++
+    /// caller can switch on the same discriminants the `extern "C"`
+    /// surface's `crusty_last_errno()` returns instead of pattern-matching
+    /// the thrown exception's `what()` string. See `BridgeError`.
+    #[derive(Debug)]
+    enum CryptoErrorCode {
+        Success = 0,
+        InvalidParams = -1,
+        AuthenticationFailed = -2,
+        EncryptionError = -3,
+        DecryptionError = -4,
+        KeyDerivationError = -5,
+        BufferTooSmall = -6,
+        InternalError = -7,
+        HardwareNotAvailable = -8,
+        UnsupportedScheme = -9,
+        UnsupportedVersion = -10,
+        InsufficientSigners = -11,
+        InvalidShare = -12,
+        MalformedCommitment = -13,
+        InvalidIdentityElement = -14,
+    }
+
+    extern "Rust" {
+        /// Encrypts `data` with a key derived from `password` and returns
+        /// the versioned ciphertext container (see the `container` module).
+        fn bridge_encrypt(data: &[u8], password: &[u8]) -> Result<Vec<u8>>;
+
+        /// Like `bridge_encrypt`, but lets the caller pick the AEAD
+        /// algorithm (`crate::std_features::ALGORITHM_ID_AES_256_GCM`/
+        /// `_SIV`) instead of always using AES-256-GCM.
+        fn bridge_encrypt_ex(data: &[u8], password: &[u8], algorithm_id: u8) -> Result<Vec<u8>>;
+
+        /// Reverses `bridge_encrypt`. Throws a C++ exception on
+        /// authentication failure or a malformed container, rather than
+        /// returning a sentinel error code.
+        fn bridge_decrypt(data: &[u8], password: &[u8]) -> Result<Vec<u8>>;
+
+        /// Hashes `password` with Argon2id and returns the PHC string.
+        fn bridge_hash_password(password: &[u8]) -> Result<String>;
+
+        /// Like `bridge_hash_password`, but lets the caller pick the KDF
+        /// scheme (`crate::KdfScheme::Argon2id` = 0,
+        /// `crate::KdfScheme::Pbkdf2HmacSha512` = 1) instead of always
+        /// using Argon2id.
+        fn bridge_hash_password_ex(password: &[u8], scheme: u8) -> Result<String>;
+
+        /// Derives a 32-byte key from `password` and `salt` with Argon2id.
+        fn bridge_derive_key_from_password(password: &[u8], salt: &[u8]) -> Result<[u8; 32]>;
+
+        /// Like `bridge_derive_key_from_password`, but lets the caller pick
+        /// the KDF scheme. `iterations` selects the PBKDF2 round count (0
+        /// means `crate::kdf::DEFAULT_PBKDF2_ITERATIONS`); it is ignored
+        /// for Argon2id.
+        fn bridge_derive_key_from_password_ex(
+            password: &[u8],
+            salt: &[u8],
+            scheme: u8,
+            iterations: u32,
+        ) -> Result<[u8; 32]>;
+    }
+}
+
+#[cfg(feature = "std")]
+use crate::std_features::derive_key_from_password_internal;
+
+/// Error type surfaced to C++ as a thrown exception. `cxx` requires the
+/// error to implement `Display`, which becomes the exception's `what()`;
+/// `code` additionally carries the same [`crate::CryptoErrorCode`]
+/// discriminant the `extern "C"` surface returns, via the bridge's shared
+/// `ffi::CryptoErrorCode` enum, so C++ can branch on it instead of parsing
+/// the message.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct BridgeError {
+    pub code: ffi::CryptoErrorCode,
+    message: &'static str,
+}
+
+#[cfg(feature = "std")]
+impl BridgeError {
+    fn new(code: crate::CryptoErrorCode, message: &'static str) -> Self {
+        BridgeError { code: code.into(), message }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<crate::CryptoErrorCode> for ffi::CryptoErrorCode {
+    fn from(code: crate::CryptoErrorCode) -> Self {
+        match code {
+            crate::CryptoErrorCode::Success => ffi::CryptoErrorCode::Success,
+            crate::CryptoErrorCode::InvalidParams => ffi::CryptoErrorCode::InvalidParams,
+            crate::CryptoErrorCode::AuthenticationFailed => ffi::CryptoErrorCode::AuthenticationFailed,
+            crate::CryptoErrorCode::EncryptionError => ffi::CryptoErrorCode::EncryptionError,
+            crate::CryptoErrorCode::DecryptionError => ffi::CryptoErrorCode::DecryptionError,
+            crate::CryptoErrorCode::KeyDerivationError => ffi::CryptoErrorCode::KeyDerivationError,
+            crate::CryptoErrorCode::BufferTooSmall => ffi::CryptoErrorCode::BufferTooSmall,
+            crate::CryptoErrorCode::InternalError => ffi::CryptoErrorCode::InternalError,
+            crate::CryptoErrorCode::HardwareNotAvailable => ffi::CryptoErrorCode::HardwareNotAvailable,
+            crate::CryptoErrorCode::UnsupportedScheme => ffi::CryptoErrorCode::UnsupportedScheme,
+            crate::CryptoErrorCode::UnsupportedVersion => ffi::CryptoErrorCode::UnsupportedVersion,
+            crate::CryptoErrorCode::InsufficientSigners => ffi::CryptoErrorCode::InsufficientSigners,
+            crate::CryptoErrorCode::InvalidShare => ffi::CryptoErrorCode::InvalidShare,
+            crate::CryptoErrorCode::MalformedCommitment => ffi::CryptoErrorCode::MalformedCommitment,
+            crate::CryptoErrorCode::InvalidIdentityElement => ffi::CryptoErrorCode::InvalidIdentityElement,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BridgeError {}
+
+/// Maps an [`crate::std_features::encrypt_data_bytes_with_params`] error
+/// string to the [`crate::CryptoErrorCode`] that best describes it, so
+/// `bridge_encrypt`/`bridge_encrypt_ex` give C++ the same precision the
+/// bridge's shared enum promises instead of collapsing every failure to
+/// `EncryptionError`.
+#[cfg(feature = "std")]
+fn encrypt_error(err: &'static str) -> BridgeError {
+    match err {
+        "unsupported algorithm" => BridgeError::new(crate::CryptoErrorCode::UnsupportedScheme, err),
+        "parallelism must be nonzero"
+        | "memory_kib below the minimum recommended cost"
+        | "unrecognized Argon2 variant"
+        | "invalid Argon2 parameters" => BridgeError::new(crate::CryptoErrorCode::InvalidParams, err),
+        "key derivation failed" => BridgeError::new(crate::CryptoErrorCode::KeyDerivationError, err),
+        _ => BridgeError::new(crate::CryptoErrorCode::EncryptionError, "encryption failed"),
+    }
+}
+
+/// Maps a [`crate::decrypt_data_bytes`] error string to the
+/// [`crate::CryptoErrorCode`] that best describes it; see [`encrypt_error`].
+#[cfg(feature = "std")]
+fn decrypt_error(err: &'static str) -> BridgeError {
+    match err {
+        "unsupported container format version" => {
+            BridgeError::new(crate::CryptoErrorCode::UnsupportedVersion, err)
+        }
+        "unsupported algorithm" => BridgeError::new(crate::CryptoErrorCode::UnsupportedScheme, err),
+        "bad magic: not a CRUSTy-Core container"
+        | "buffer too short to contain a container header"
+        | "buffer too short to contain the declared salt"
+        | "buffer too short to contain the declared Argon2 parameters"
+        | "buffer too short to contain a nonce and length prefix"
+        | "buffer too short for the declared ciphertext length" => {
+            BridgeError::new(crate::CryptoErrorCode::InvalidParams, err)
+        }
+        "parallelism must be nonzero"
+        | "memory_kib below the minimum recommended cost"
+        | "unrecognized Argon2 variant"
+        | "invalid Argon2 parameters"
+        | "key derivation failed" => BridgeError::new(crate::CryptoErrorCode::KeyDerivationError, err),
+        _ => BridgeError::new(crate::CryptoErrorCode::AuthenticationFailed, "authentication failed"),
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn bridge_encrypt(data: &[u8], password: &[u8]) -> Result<Vec<u8>, BridgeError> {
+    crate::encrypt_data_bytes(data, password).map_err(encrypt_error)
+}
+
+#[cfg(feature = "std")]
+pub fn bridge_encrypt_ex(data: &[u8], password: &[u8], algorithm_id: u8) -> Result<Vec<u8>, BridgeError> {
+    crate::std_features::encrypt_data_bytes_ex(data, password, algorithm_id).map_err(encrypt_error)
+}
+
+#[cfg(feature = "std")]
+pub fn bridge_decrypt(data: &[u8], password: &[u8]) -> Result<Vec<u8>, BridgeError> {
+    crate::decrypt_data_bytes(data, password).map_err(decrypt_error)
+}
+
+#[cfg(feature = "std")]
+pub fn bridge_hash_password(password: &[u8]) -> Result<String, BridgeError> {
+    use argon2::{
+        password_hash::{rand_core::OsRng as Argon2OsRng, PasswordHasher, SaltString},
+        Argon2,
+    };
+
+    let salt = SaltString::generate(&mut Argon2OsRng);
+    Argon2::default()
+        .hash_password(password, &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|_| BridgeError::new(crate::CryptoErrorCode::KeyDerivationError, "key derivation failed"))
+}
+
+/// Like [`bridge_hash_password`], but lets the caller pick the KDF scheme
+/// (see [`crate::KdfScheme`]) instead of always using Argon2id, matching
+/// the `_ex` pattern the `extern "C"` surface already uses for
+/// `hash_password_ex`.
+#[cfg(feature = "std")]
+pub fn bridge_hash_password_ex(password: &[u8], scheme: u8) -> Result<String, BridgeError> {
+    let Some(scheme) = crate::KdfScheme::from_u8(scheme) else {
+        return Err(BridgeError::new(crate::CryptoErrorCode::UnsupportedScheme, "unsupported scheme"));
+    };
+
+    match scheme {
+        crate::KdfScheme::Argon2id => bridge_hash_password(password),
+        crate::KdfScheme::Pbkdf2HmacSha512 => {
+            Ok(crate::kdf::hash_password_pbkdf2(password, crate::kdf::DEFAULT_PBKDF2_ITERATIONS))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn bridge_derive_key_from_password(
+    password: &[u8],
+    salt: &[u8],
+) -> Result<[u8; 32], BridgeError> {
+    derive_key_from_password_internal(password, salt)
+        .map_err(|_| BridgeError::new(crate::CryptoErrorCode::KeyDerivationError, "key derivation failed"))
+}
+
+/// Like [`bridge_derive_key_from_password`], but lets the caller pick the
+/// KDF scheme; see [`bridge_hash_password_ex`].
+#[cfg(feature = "std")]
+pub fn bridge_derive_key_from_password_ex(
+    password: &[u8],
+    salt: &[u8],
+    scheme: u8,
+    iterations: u32,
+) -> Result<[u8; 32], BridgeError> {
+    let Some(scheme) = crate::KdfScheme::from_u8(scheme) else {
+        return Err(BridgeError::new(crate::CryptoErrorCode::UnsupportedScheme, "unsupported scheme"));
+    };
+
+    match scheme {
+        crate::KdfScheme::Argon2id => bridge_derive_key_from_password(password, salt),
+        crate::KdfScheme::Pbkdf2HmacSha512 => {
+            let iterations = if iterations == 0 { crate::kdf::DEFAULT_PBKDF2_ITERATIONS } else { iterations };
+            let mut key = [0u8; 32];
+            crate::kdf::derive_key_pbkdf2(password, salt, iterations, &mut key);
+            Ok(key)
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! FROST (Flexible Round-Optimized Schnorr Threshold) signatures over
+//! Ristretto255.
+//!
+//! Lets `threshold`-of-`total_shares` key holders jointly produce a single
+//! Schnorr signature without ever reconstructing the signing key in one
+//! place. [`keygen`] is a trusted-dealer variant (the dealer knows the
+//! signing key and splits it), in the same spirit as [`crate::sharing`]'s
+//! Shamir split -- just over the Ristretto scalar field instead of
+//! GF(2^8), since a signature share is computed by scalar arithmetic, not
+//! reassembled from raw secret bytes.
+//!
+//! Signing is two rounds, per the FROST paper:
+//!
+//! 1. **Commit**: each signer draws a hiding nonce `d` and binding nonce
+//!    `e` and publishes commitments `D = d*G`, `E = e*G` ([`commit`]).
+//! 2. **Sign**: given the message and every participating signer's
+//!    commitments, each signer computes a per-signer binding factor
+//!    `rho_i = H1(i, msg, commitment_list)`, the group commitment
+//!    `R = sum(D_i + rho_i*E_i)`, the challenge `c = H2(R, groupPubKey,
+//!    msg)`, and a signature share `z_i = d_i + rho_i*e_i + lambda_i*c*s_i`
+//!    where `lambda_i` is `i`'s Lagrange coefficient over the signing set
+//!    ([`sign`]).
+//!
+//! [`aggregate`] sums the `z_i` into `z` and returns `(R, z)`; [`verify`]
+//! checks it like any other Ristretto Schnorr signature: `R == z*G -
+//! c*Y`.
+
+#[cfg(feature = "std")]
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+#[cfg(feature = "std")]
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+#[cfg(feature = "std")]
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::RngCore;
+#[cfg(feature = "std")]
+use sha2::{Digest, Sha512};
+#[cfg(feature = "std")]
+use zeroize::Zeroize;
+
+/// One participant's key share from a [`keygen`] split: their Shamir
+/// share of the signing key, the threshold it was split under (needed by
+/// [`sign`]/[`aggregate`] to reject an under-sized signing set), and the
+/// public verification share other participants can check their
+/// signature share against. `secret_share` is zeroized on drop.
+#[cfg(feature = "std")]
+pub struct KeyShare {
+    pub index: u8,
+    pub threshold: u8,
+    pub secret_share: Scalar,
+    pub verification_share: RistrettoPoint,
+}
+
+#[cfg(feature = "std")]
+impl Drop for KeyShare {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+/// The secret half of a signer's round-1 output: the hiding and binding
+/// nonces, kept by the signer and consumed by their own [`sign`] call.
+/// Zeroized on drop -- a leaked nonce pair is as sensitive as the signing
+/// key share itself (FROST, like Schnorr generally, is not nonce-misuse
+/// resistant).
+#[cfg(feature = "std")]
+pub struct SigningNonces {
+    pub hiding: Scalar,
+    pub binding: Scalar,
+}
+
+#[cfg(feature = "std")]
+impl Drop for SigningNonces {
+    fn drop(&mut self) {
+        self.hiding.zeroize();
+        self.binding.zeroize();
+    }
+}
+
+/// The public half of a signer's round-1 output: published to the
+/// aggregator (and every other signer) alongside `index` so round 2 can
+/// compute the binding factors and group commitment.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy)]
+pub struct SigningCommitment {
+    pub index: u8,
+    pub hiding: RistrettoPoint,
+    pub binding: RistrettoPoint,
+}
+
+/// Splits a fresh random signing key into `total_shares` Shamir shares
+/// over the Ristretto scalar field, any `threshold` of which can sign via
+/// [`sign`]/[`aggregate`]. Returns the group's public key alongside the
+/// shares. This is a trusted-dealer split: whoever calls this function
+/// sees the full signing key transiently, the same trust model
+/// [`crate::sharing::split_secret`] uses for AES keys.
+pub fn keygen(threshold: u8, total_shares: u8) -> Result<(RistrettoPoint, std::vec::Vec<KeyShare>), &'static str> {
+    if threshold == 0 {
+        return Err("threshold must be nonzero");
+    }
+    if total_shares == 0 {
+        return Err("total_shares must be nonzero");
+    }
+    if threshold > total_shares {
+        return Err("threshold must not exceed total_shares");
+    }
+
+    let degree = (threshold - 1) as usize;
+
+    // Random polynomial over the scalar field; the constant term is the
+    // signing key, the rest are random, exactly like `sharing::split_secret`
+    // except the field is the Ristretto group order instead of GF(2^8).
+    let mut coefficients = std::vec::Vec::with_capacity(degree + 1);
+    for _ in 0..=degree {
+        coefficients.push(random_scalar());
+    }
+    let group_public = RISTRETTO_BASEPOINT_POINT * coefficients[0];
+
+    let mut shares = std::vec::Vec::with_capacity(total_shares as usize);
+    for x in 1..=total_shares {
+        let secret_share = eval_polynomial(&coefficients, Scalar::from(x as u64));
+        let verification_share = RISTRETTO_BASEPOINT_POINT * secret_share;
+        shares.push(KeyShare { index: x, threshold, secret_share, verification_share });
+    }
+
+    for coefficient in coefficients.iter_mut() {
+        coefficient.zeroize();
+    }
+
+    Ok((group_public, shares))
+}
+
+/// Round 1: draws fresh hiding/binding nonces and returns them alongside
+/// the commitments to publish. `index` must match the [`KeyShare::index`]
+/// this nonce pair will be used to sign with in [`sign`].
+pub fn commit(index: u8) -> (SigningNonces, SigningCommitment) {
+    let hiding = random_scalar();
+    let binding = random_scalar();
+    let commitment = SigningCommitment {
+        index,
+        hiding: RISTRETTO_BASEPOINT_POINT * hiding,
+        binding: RISTRETTO_BASEPOINT_POINT * binding,
+    };
+    (SigningNonces { hiding, binding }, commitment)
+}
+
+/// Round 2: computes this signer's signature share `z_i`, given their own
+/// [`KeyShare`] and [`SigningNonces`], the message, the group public key,
+/// and every participating signer's [`SigningCommitment`] (including
+/// their own). `commitments` must be the same list, in the same order,
+/// that every other participating signer uses -- the group commitment and
+/// challenge are computed over it, so a mismatch produces a share that
+/// won't aggregate into a valid signature.
+pub fn sign(
+    key_share: &KeyShare,
+    nonces: &SigningNonces,
+    message: &[u8],
+    group_public: &RistrettoPoint,
+    commitments: &[SigningCommitment],
+) -> Result<Scalar, &'static str> {
+    if commitments.len() < key_share.threshold as usize {
+        return Err("fewer signers than the key share's threshold");
+    }
+    if !commitments.iter().any(|c| c.index == key_share.index) {
+        return Err("signer's own commitment is missing from the commitment list");
+    }
+    if has_duplicate_index(commitments) {
+        return Err("duplicate signer index in commitment list");
+    }
+
+    let binding_factors = binding_factors(message, commitments);
+    let group_commitment = compute_group_commitment(commitments, &binding_factors);
+    let challenge = challenge(&group_commitment, group_public, message);
+    let lambda_i = lagrange_coefficient(key_share.index, commitments);
+    let rho_i = binding_factors
+        .iter()
+        .find(|(index, _)| *index == key_share.index)
+        .map(|(_, rho)| *rho)
+        .ok_or("signer's own binding factor is missing")?;
+
+    Ok(nonces.hiding + rho_i * nonces.binding + lambda_i * challenge * key_share.secret_share)
+}
+
+/// Aggregates signature shares (each the `(index, z_i)` pair signer
+/// `index` returned from [`sign`]) into the final Schnorr signature
+/// `(R, z)`. `commitments` must be the exact list [`sign`] was called
+/// with.
+pub fn aggregate(
+    message: &[u8],
+    group_public: &RistrettoPoint,
+    threshold: u8,
+    commitments: &[SigningCommitment],
+    signature_shares: &[(u8, Scalar)],
+) -> Result<(RistrettoPoint, Scalar), &'static str> {
+    if commitments.len() < threshold as usize {
+        return Err("fewer signers than the key's threshold");
+    }
+    if signature_shares.len() != commitments.len() {
+        return Err("signature share count doesn't match commitment count");
+    }
+    if has_duplicate_index(commitments) {
+        return Err("duplicate signer index in commitment list");
+    }
+
+    let binding_factors = binding_factors(message, commitments);
+    let group_commitment = compute_group_commitment(commitments, &binding_factors);
+
+    let mut z = Scalar::ZERO;
+    for &(index, share) in signature_shares {
+        if !commitments.iter().any(|c| c.index == index) {
+            return Err("signature share index not present in the commitment list");
+        }
+        z += share;
+    }
+
+    Ok((group_commitment, z))
+}
+
+/// Verifies a Ristretto Schnorr signature `(r, z)` against `group_public`
+/// and `message`: checks `r == z*G - c*groupPublic` where `c =
+/// H2(r, groupPublic, msg)`.
+pub fn verify(message: &[u8], group_public: &RistrettoPoint, signature: &(RistrettoPoint, Scalar)) -> bool {
+    let (r, z) = signature;
+    let c = challenge(r, group_public, message);
+    *r == RISTRETTO_BASEPOINT_POINT * z - c * group_public
+}
+
+/// Canonical compressed encoding of the Ristretto identity element (the
+/// point at infinity): all-zero bytes. See [`decompress_point`]/
+/// [`compress_point`].
+const IDENTITY_ENCODING: [u8; 32] = [0u8; 32];
+
+/// Decompresses a 32-byte Ristretto point, rejecting a buffer that isn't
+/// a valid canonical encoding of a group element, *and* rejecting the
+/// identity element itself. A commitment or public key that deserializes
+/// to the identity lets an adversary cancel a signer's contribution to a
+/// sum (e.g. substitute it for a [`SigningCommitment`] to force a
+/// predictable group commitment) or collapse a verification equation, so
+/// it's treated as invalid rather than a legal-but-degenerate point.
+pub fn decompress_point(bytes: &[u8; 32]) -> Result<RistrettoPoint, &'static str> {
+    if *bytes == IDENTITY_ENCODING {
+        return Err("identity element is not a valid point encoding");
+    }
+    CompressedRistretto(*bytes).decompress().ok_or("malformed commitment: not a valid Ristretto point")
+}
+
+/// Compresses a Ristretto point for the wire, refusing to emit the
+/// identity element -- the encode-side counterpart of [`decompress_point`]'s
+/// rejection.
+pub fn compress_point(point: &RistrettoPoint) -> Result<[u8; 32], &'static str> {
+    let bytes = point.compress().to_bytes();
+    if bytes == IDENTITY_ENCODING {
+        return Err("refusing to serialize the identity element");
+    }
+    Ok(bytes)
+}
+
+/// Decodes a 32-byte scalar, rejecting a buffer that isn't the canonical
+/// (reduced) encoding of a scalar field element -- the FFI layer's wire
+/// format for a [`KeyShare::secret_share`] or a signature share.
+pub fn decode_scalar(bytes: &[u8; 32]) -> Result<Scalar, &'static str> {
+    Option::from(Scalar::from_canonical_bytes(*bytes)).ok_or("malformed scalar: not a canonical encoding")
+}
+
+fn random_scalar() -> Scalar {
+    let mut bytes = [0u8; 64];
+    OsRng.fill_bytes(&mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+/// Evaluates a polynomial (lowest-degree coefficient first) at `x` via
+/// Horner's method over the scalar field.
+fn eval_polynomial(coefficients: &[Scalar], x: Scalar) -> Scalar {
+    let mut result = Scalar::ZERO;
+    for coefficient in coefficients.iter().rev() {
+        result = result * x + coefficient;
+    }
+    result
+}
+
+fn has_duplicate_index(commitments: &[SigningCommitment]) -> bool {
+    for (i, a) in commitments.iter().enumerate() {
+        for b in &commitments[i + 1..] {
+            if a.index == b.index {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Per-signer binding factor `rho_i = H1(i, msg, commitment_list)`,
+/// binding every signer's share to the full commitment list so a
+/// malicious aggregator can't swap in different commitments for
+/// different signers (Wagner's attack on naive two-round Schnorr
+/// multisignatures).
+fn binding_factors(message: &[u8], commitments: &[SigningCommitment]) -> std::vec::Vec<(u8, Scalar)> {
+    let mut transcript = std::vec::Vec::new();
+    for c in commitments {
+        transcript.push(c.index);
+        transcript.extend_from_slice(c.hiding.compress().as_bytes());
+        transcript.extend_from_slice(c.binding.compress().as_bytes());
+    }
+
+    commitments
+        .iter()
+        .map(|c| {
+            let mut hasher = Sha512::new();
+            hasher.update(b"FROST-ristretto255-binding-factor");
+            hasher.update([c.index]);
+            hasher.update(message);
+            hasher.update(&transcript);
+            (c.index, Scalar::from_bytes_mod_order_wide(&hasher.finalize().into()))
+        })
+        .collect()
+}
+
+/// Group commitment `R = sum(D_i + rho_i*E_i)` over every participating
+/// signer.
+fn compute_group_commitment(commitments: &[SigningCommitment], binding_factors: &[(u8, Scalar)]) -> RistrettoPoint {
+    commitments
+        .iter()
+        .map(|c| {
+            let rho_i = binding_factors.iter().find(|(index, _)| *index == c.index).map(|(_, rho)| *rho).unwrap();
+            c.hiding + rho_i * c.binding
+        })
+        .sum()
+}
+
+/// Schnorr challenge `c = H2(R, groupPublic, msg)`.
+fn challenge(group_commitment: &RistrettoPoint, group_public: &RistrettoPoint, message: &[u8]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(b"FROST-ristretto255-challenge");
+    hasher.update(group_commitment.compress().as_bytes());
+    hasher.update(group_public.compress().as_bytes());
+    hasher.update(message);
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+/// Lagrange coefficient for signer `index` at x=0, over the set of
+/// indices in `commitments` -- the scalar-field analogue of
+/// `sharing::lagrange_interpolate_at_zero`.
+fn lagrange_coefficient(index: u8, commitments: &[SigningCommitment]) -> Scalar {
+    let x_i = Scalar::from(index as u64);
+    let mut numerator = Scalar::ONE;
+    let mut denominator = Scalar::ONE;
+    for c in commitments {
+        if c.index == index {
+            continue;
+        }
+        let x_j = Scalar::from(c.index as u64);
+        numerator *= x_j;
+        denominator *= x_j - x_i;
+    }
+    numerator * denominator.invert()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decompress_point_rejects_identity_encoding() {
+        let result = decompress_point(&[0u8; 32]);
+        assert_eq!(result, Err("identity element is not a valid point encoding"));
+    }
+
+    #[test]
+    fn compress_point_rejects_identity_element() {
+        use curve25519_dalek::traits::Identity;
+        let result = compress_point(&RistrettoPoint::identity());
+        assert_eq!(result, Err("refusing to serialize the identity element"));
+    }
+}
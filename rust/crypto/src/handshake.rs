@@ -0,0 +1,258 @@
+//! UKEY2-style authenticated key-agreement handshake.
+//!
+//! Every other entry point in this crate is password-based, which leaves
+//! no way for two peers (e.g. a PC build and an STM32H573 build) to agree
+//! on a key over an untrusted channel. This module adds a three-message
+//! handshake modeled on Google's UKEY2:
+//!
+//! 1. **ClientInit** (initiator -> responder): only a SHA-512 commitment
+//!    to the initiator's ephemeral X25519 public key -- the key itself is
+//!    *not* sent yet.
+//! 2. **ServerInit** (responder -> initiator): the responder's ephemeral
+//!    X25519 public key.
+//! 3. **ClientFinish** (initiator -> responder): the initiator's real
+//!    ephemeral public key, letting the responder check it against the
+//!    commitment from message 1 before using it for key agreement.
+//!
+//! The commitment has to cover the initiator's own public key, not just
+//! some unrelated random value: the point of message 1 is to fix the
+//! initiator's key *before* the responder's key is known, so that an
+//! active attacker sitting between the two peers can't swap either side's
+//! key in transit without either breaking the commitment check in message
+//! 3 or being unable to produce one at all (SHA-512 preimage resistance).
+//! A commitment to an unrelated nonce would let an attacker rewrite
+//! `ClientInit`'s public key freely, since nothing would ever notice.
+//!
+//! Both sides then compute the X25519 shared secret and run HKDF-SHA256
+//! over it, with the full message transcript (commitment, `ServerInit`,
+//! and the revealed public key) as the `info` parameter, to derive a
+//! short human-verifiable authentication string (for out-of-band
+//! comparison) and a 32-byte session key usable directly with
+//! [`crate::encrypt_data`]/[`crate::decrypt_data`].
+
+#[cfg(feature = "std")]
+use hkdf::Hkdf;
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::RngCore;
+#[cfg(feature = "std")]
+use sha2::{Digest, Sha256, Sha512};
+#[cfg(feature = "std")]
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+const COMMITMENT_LEN: usize = 64;
+const AUTH_STRING_LEN: usize = 6;
+
+/// State held by the initiator between `client_init` and `client_finish`.
+#[cfg(feature = "std")]
+pub struct ClientState {
+    secret: EphemeralSecret,
+    public: PublicKey,
+    client_init_message: std::vec::Vec<u8>,
+}
+
+/// State held by the responder between `server_init` and `server_finish`.
+#[cfg(feature = "std")]
+pub struct ServerState {
+    secret: EphemeralSecret,
+    commitment: [u8; COMMITMENT_LEN],
+    client_init_message: std::vec::Vec<u8>,
+    server_init_message: std::vec::Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+/// Starts the handshake as the initiator, returning the `ClientInit`
+/// message (a SHA-512 commitment to the initiator's own public key) to
+/// send to the responder. The public key itself is withheld until
+/// `client_finish`, so an attacker who tampers with it in transit can't
+/// produce a matching commitment.
+pub fn client_init() -> (ClientState, std::vec::Vec<u8>) {
+    let secret = EphemeralSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&secret);
+
+    let commitment = Sha512::digest(public.as_bytes());
+    let message = commitment.to_vec();
+
+    let state = ClientState { secret, public, client_init_message: message.clone() };
+    (state, message)
+}
+
+#[cfg(feature = "std")]
+/// Accepts a `ClientInit` message as the responder, returning the
+/// `ServerInit` message (`server_pub(32)`) to send back. The initiator's
+/// public key isn't known yet -- only its commitment -- so key agreement
+/// can't happen until `server_finish` reveals it.
+pub fn server_init(client_init_message: &[u8]) -> Result<(ServerState, std::vec::Vec<u8>), &'static str> {
+    if client_init_message.len() != COMMITMENT_LEN {
+        return Err("malformed ClientInit message");
+    }
+    let mut commitment = [0u8; COMMITMENT_LEN];
+    commitment.copy_from_slice(client_init_message);
+
+    let secret = EphemeralSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&secret);
+    let server_init_message = public.as_bytes().to_vec();
+
+    let state = ServerState {
+        secret,
+        commitment,
+        client_init_message: client_init_message.to_vec(),
+        server_init_message: server_init_message.clone(),
+    };
+    Ok((state, server_init_message))
+}
+
+#[cfg(feature = "std")]
+/// Finishes the handshake as the initiator: computes the shared secret
+/// against the responder's `ServerInit` message and returns the
+/// `ClientFinish` message (the initiator's real public key, committed to
+/// in `ClientInit`), the derived auth string, and the derived session key.
+pub fn client_finish(
+    state: ClientState,
+    server_init_message: &[u8],
+) -> Result<(std::vec::Vec<u8>, [u8; AUTH_STRING_LEN], [u8; 32]), &'static str> {
+    if server_init_message.len() != 32 {
+        return Err("malformed ServerInit message");
+    }
+    let server_public = PublicKey::from(<[u8; 32]>::try_from(server_init_message).unwrap());
+    let shared_secret = state.secret.diffie_hellman(&server_public);
+
+    let client_finish_message = state.public.as_bytes().to_vec();
+    let (auth_string, session_key) = derive(
+        shared_secret.as_bytes(),
+        &state.client_init_message,
+        server_init_message,
+        &client_finish_message,
+    );
+
+    Ok((client_finish_message, auth_string, session_key))
+}
+
+#[cfg(feature = "std")]
+/// Finishes the handshake as the responder: verifies that `client_finish`
+/// (the initiator's revealed public key) matches the commitment from
+/// `ClientInit`, and on success uses that key to compute the shared
+/// secret and derive the same auth string and session key the initiator
+/// derived.
+pub fn server_finish(
+    state: ServerState,
+    client_finish_message: &[u8],
+) -> Result<([u8; AUTH_STRING_LEN], [u8; 32]), &'static str> {
+    if client_finish_message.len() != 32 {
+        return Err("malformed ClientFinish message");
+    }
+    if Sha512::digest(client_finish_message).as_slice() != state.commitment {
+        return Err("commitment mismatch");
+    }
+
+    let client_public = PublicKey::from(<[u8; 32]>::try_from(client_finish_message).unwrap());
+    let shared_secret = state.secret.diffie_hellman(&client_public);
+    Ok(derive(
+        shared_secret.as_bytes(),
+        &state.client_init_message,
+        &state.server_init_message,
+        client_finish_message,
+    ))
+}
+
+/// Role-tagged handshake state, boxed and handed across the FFI boundary
+/// as an opaque pointer so a single `handshake_finish` entry point can
+/// dispatch to the right role internally instead of exposing two
+/// differently-shaped finish functions.
+#[cfg(feature = "std")]
+pub enum HandshakeState {
+    Initiator(ClientState),
+    Responder(ServerState),
+}
+
+#[cfg(feature = "std")]
+/// Finishes the handshake regardless of role. For the initiator,
+/// `peer_message` is the `ServerInit` message and the returned
+/// `outgoing_message` is the `ClientFinish` payload to send on. For the
+/// responder, `peer_message` is the `ClientFinish` payload and
+/// `outgoing_message` is `None` since the responder has nothing left to
+/// send.
+pub fn finish(
+    state: HandshakeState,
+    peer_message: &[u8],
+) -> Result<(Option<std::vec::Vec<u8>>, [u8; AUTH_STRING_LEN], [u8; 32]), &'static str> {
+    match state {
+        HandshakeState::Initiator(state) => {
+            let (outgoing, auth_string, session_key) = client_finish(state, peer_message)?;
+            Ok((Some(outgoing), auth_string, session_key))
+        }
+        HandshakeState::Responder(state) => {
+            let (auth_string, session_key) = server_finish(state, peer_message)?;
+            Ok((None, auth_string, session_key))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn derive(
+    shared_secret: &[u8],
+    client_init_message: &[u8],
+    server_init_message: &[u8],
+    client_finish_message: &[u8],
+) -> ([u8; AUTH_STRING_LEN], [u8; 32]) {
+    let mut transcript = std::vec::Vec::with_capacity(
+        client_init_message.len() + server_init_message.len() + client_finish_message.len(),
+    );
+    transcript.extend_from_slice(client_init_message);
+    transcript.extend_from_slice(server_init_message);
+    transcript.extend_from_slice(client_finish_message);
+
+    let hk = Hkdf::<Sha256>::new(None, shared_secret);
+    let mut okm = [0u8; AUTH_STRING_LEN + 32];
+    hk.expand(&transcript, &mut okm).expect("okm length is valid for HKDF-SHA256");
+
+    let mut auth_string = [0u8; AUTH_STRING_LEN];
+    auth_string.copy_from_slice(&okm[..AUTH_STRING_LEN]);
+    let mut session_key = [0u8; 32];
+    session_key.copy_from_slice(&okm[AUTH_STRING_LEN..]);
+    (auth_string, session_key)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_handshake_agrees_on_auth_string_and_session_key() {
+        let (client_state, client_init_message) = client_init();
+        let (server_state, server_init_message) = server_init(&client_init_message).unwrap();
+        let (client_finish_message, client_auth, client_key) =
+            client_finish(client_state, &server_init_message).unwrap();
+        let (server_auth, server_key) = server_finish(server_state, &client_finish_message).unwrap();
+
+        assert_eq!(client_auth, server_auth);
+        assert_eq!(client_key, server_key);
+    }
+
+    #[test]
+    fn server_finish_rejects_a_tampered_client_finish() {
+        let (client_state, client_init_message) = client_init();
+        let (server_state, server_init_message) = server_init(&client_init_message).unwrap();
+        let (mut client_finish_message, _, _) = client_finish(client_state, &server_init_message).unwrap();
+        client_finish_message[0] ^= 0xff;
+
+        assert_eq!(server_finish(server_state, &client_finish_message), Err("commitment mismatch"));
+    }
+
+    #[test]
+    fn server_finish_rejects_a_swapped_public_key() {
+        // An active attacker can't splice a different initiator's real
+        // public key into ClientFinish: ClientInit only ever carried a
+        // commitment to the genuine key, so the swapped key fails the
+        // commitment check instead of silently being accepted.
+        let (client_state, client_init_message) = client_init();
+        let (server_state, server_init_message) = server_init(&client_init_message).unwrap();
+        let (_, _, _) = client_finish(client_state, &server_init_message).unwrap();
+
+        let (attacker_state, _) = client_init();
+        let (attacker_finish_message, _, _) = client_finish(attacker_state, &server_init_message).unwrap();
+
+        assert_eq!(server_finish(server_state, &attacker_finish_message), Err("commitment mismatch"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Password-hashing and key-derivation schemes.
+//!
+//! `hash_password`/`derive_key_from_password` originally hard-coded
+//! Argon2id. This module adds a second scheme, PBKDF2-HMAC-SHA512, and a
+//! [`KdfScheme`] selector so callers can pick one at runtime -- important
+//! for interop with systems that standardized on PBKDF2-SHA512 for stored
+//! credentials. [`Argon2Params`] further lets Argon2id's own cost
+//! (memory/iterations/parallelism) be tuned per call instead of always
+//! using `Argon2::default()`.
+
+#[cfg(feature = "std")]
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine as _};
+#[cfg(feature = "std")]
+use hmac::Hmac;
+#[cfg(feature = "std")]
+use pbkdf2::pbkdf2;
+#[cfg(feature = "std")]
+use rand::RngCore;
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use sha2::Sha512;
+#[cfg(feature = "std")]
+use subtle::ConstantTimeEq;
+#[cfg(feature = "std")]
+use argon2::{Algorithm, Argon2, Params, Version};
+
+/// Default PBKDF2 iteration count. Matches OWASP's current PBKDF2-SHA512
+/// recommendation.
+pub const DEFAULT_PBKDF2_ITERATIONS: u32 = 210_000;
+
+const PBKDF2_SALT_LEN: usize = 16;
+const PBKDF2_OUTPUT_LEN: usize = 64;
+
+/// Key-derivation scheme selector, carried as a `u8` across the FFI
+/// boundary so C callers can choose a scheme without linking against an
+/// enum definition.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KdfScheme {
+    Argon2id = 0,
+    Pbkdf2HmacSha512 = 1,
+}
+
+impl KdfScheme {
+    pub fn from_u8(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(KdfScheme::Argon2id),
+            1 => Some(KdfScheme::Pbkdf2HmacSha512),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// Hashes `password` with PBKDF2-HMAC-SHA512 and returns a self-describing
+/// PHC string: `$pbkdf2-sha512$<iterations>$<base64-salt>$<base64-hash>`.
+pub fn hash_password_pbkdf2(password: &[u8], iterations: u32) -> std::string::String {
+    let mut salt = [0u8; PBKDF2_SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let mut derived = [0u8; PBKDF2_OUTPUT_LEN];
+    pbkdf2::<Hmac<Sha512>>(password, &salt, iterations, &mut derived);
+
+    std::format!(
+        "$pbkdf2-sha512${}${}${}",
+        iterations,
+        STANDARD_NO_PAD.encode(salt),
+        STANDARD_NO_PAD.encode(derived)
+    )
+}
+
+#[cfg(feature = "std")]
+/// Verifies `password` against a PHC string produced by
+/// [`hash_password_pbkdf2`], re-running PBKDF2 with the embedded
+/// iteration count and salt and comparing in constant time.
+pub fn verify_password_pbkdf2(password: &[u8], phc: &str) -> bool {
+    let mut fields = phc.split('$');
+    // phc starts with '$', so the first split segment is empty.
+    let empty = fields.next();
+    let scheme = fields.next();
+    let iterations = fields.next();
+    let salt_b64 = fields.next();
+    let hash_b64 = fields.next();
+
+    let (Some(""), Some("pbkdf2-sha512"), Some(iterations), Some(salt_b64), Some(hash_b64)) =
+        (empty, scheme, iterations, salt_b64, hash_b64)
+    else {
+        return false;
+    };
+
+    let Ok(iterations) = iterations.parse::<u32>() else {
+        return false;
+    };
+    let Ok(salt) = STANDARD_NO_PAD.decode(salt_b64) else {
+        return false;
+    };
+    let Ok(expected) = STANDARD_NO_PAD.decode(hash_b64) else {
+        return false;
+    };
+
+    let mut derived = std::vec![0u8; expected.len()];
+    pbkdf2::<Hmac<Sha512>>(password, &salt, iterations, &mut derived);
+
+    derived.ct_eq(&expected).into()
+}
+
+#[cfg(feature = "std")]
+/// Derives a `key_len`-byte key from `password` with PBKDF2-HMAC-SHA512
+/// using the given salt and iteration count, writing exactly `key_len`
+/// bytes (PBKDF2's output is as long as requested, so no HKDF-expand
+/// step is needed here).
+pub fn derive_key_pbkdf2(password: &[u8], salt: &[u8], iterations: u32, key_out: &mut [u8]) {
+    pbkdf2::<Hmac<Sha512>>(password, salt, iterations, key_out);
+}
+
+/// Tunable Argon2 cost parameters, passed across the FFI boundary as a
+/// `#[repr(C)]` struct instead of baking in `Argon2::default()`'s fixed
+/// cost, which is too heavy for embedded targets and often too light for
+/// high-value desktop secrets. Mirrors devolutions-crypto's
+/// `Argon2Parameters`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct Argon2Params {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// `0` = Argon2d, `1` = Argon2i, `2` = Argon2id -- matches the
+    /// `argon2` crate's `Algorithm` tag.
+    pub variant: u8,
+}
+
+impl Argon2Params {
+    /// RFC 9106's "first recommended option" floor (19 MiB / 2 iterations
+    /// / 1 lane), used both as the validation floor below and as the cost
+    /// assumed for older container headers (which always used
+    /// `Argon2::default()`, whose cost matches this exactly).
+    pub const MIN_MEMORY_KIB: u32 = 19 * 1024;
+
+    /// The cost `Argon2::default()` used before per-call parameters
+    /// existed. Older container headers carry no persisted parameters, so
+    /// decryption falls back to this.
+    #[cfg(feature = "std")]
+    pub fn default_cost() -> Self {
+        Argon2Params { memory_kib: Self::MIN_MEMORY_KIB, iterations: 2, parallelism: 1, variant: 2 }
+    }
+
+    fn algorithm(&self) -> Result<Algorithm, &'static str> {
+        match self.variant {
+            0 => Ok(Algorithm::Argon2d),
+            1 => Ok(Algorithm::Argon2i),
+            2 => Ok(Algorithm::Argon2id),
+            _ => Err("unrecognized Argon2 variant"),
+        }
+    }
+
+    /// Validates the parameters and builds an `Argon2` instance from
+    /// them, rejecting `parallelism == 0` and memory below
+    /// [`Self::MIN_MEMORY_KIB`] rather than letting the underlying crate
+    /// silently clamp them.
+    #[cfg(feature = "std")]
+    pub(crate) fn build(&self) -> Result<Argon2<'static>, &'static str> {
+        if self.parallelism == 0 {
+            return Err("parallelism must be nonzero");
+        }
+        if self.memory_kib < Self::MIN_MEMORY_KIB {
+            return Err("memory_kib below the minimum recommended cost");
+        }
+        let algorithm = self.algorithm()?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+            .map_err(|_| "invalid Argon2 parameters")?;
+        Ok(Argon2::new(algorithm, Version::V0x13, params))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pbkdf2_round_trip() {
+        let phc = hash_password_pbkdf2(b"correct horse battery staple", 10_000);
+        assert!(verify_password_pbkdf2(b"correct horse battery staple", &phc));
+    }
+
+    #[test]
+    fn pbkdf2_rejects_wrong_password() {
+        let phc = hash_password_pbkdf2(b"correct horse battery staple", 10_000);
+        assert!(!verify_password_pbkdf2(b"wrong password", &phc));
+    }
+}
@@ -34,6 +34,11 @@ use aes_gcm::{
     Key, Nonce
 };
 
+// Scrubs key material and other sensitive buffers from memory once
+// they're no longer needed, instead of leaving them for whatever reuses
+// that stack/heap slot next.
+use zeroize::Zeroize;
+
 // AES implementation - requires 'aes' feature
 use aes_gcm::AesGcm;
 use aes::Aes256;
@@ -44,6 +49,55 @@ use generic_array::typenum::{U12, U16};
 // Type alias for AES-256 in GCM mode with 12-byte nonce
 type Aes256Gcm = AesGcm<Aes256, U12, U16>;
 
+// Typed C++ bridge (see module docs); only meaningful for std targets that
+// link against a C++ toolchain.
+#[cfg(feature = "std")]
+mod ffi;
+
+// Selectable key-derivation schemes (Argon2id, PBKDF2-HMAC-SHA512).
+#[cfg(feature = "std")]
+mod kdf;
+#[cfg(feature = "std")]
+pub use kdf::KdfScheme;
+#[cfg(feature = "std")]
+pub use kdf::Argon2Params;
+
+// Password-less authenticated key agreement (UKEY2-style handshake).
+#[cfg(feature = "std")]
+mod handshake;
+
+// Versioned, self-describing ciphertext container (header carrying the
+// KDF salt, algorithm tag, and format version).
+#[cfg(feature = "std")]
+mod container;
+
+#[cfg(feature = "std")]
+use aes_gcm_siv::Aes256GcmSiv;
+
+// Shamir secret sharing for M-of-N master-key backup.
+#[cfg(feature = "std")]
+mod sharing;
+
+// Hybrid asymmetric (X25519 ECDH) encryption for key-at-rest workflows.
+#[cfg(feature = "std")]
+mod asymmetric;
+
+// Chunked streaming AEAD for plaintexts too large to buffer in memory at
+// once; the embedded equivalent lives in `embedded_features` below since
+// it needs `heapless` buffers instead of `Vec`.
+#[cfg(feature = "std")]
+mod streaming;
+
+// FROST threshold Schnorr signatures over Ristretto255, for M-of-N
+// signing authority instead of `sharing`'s M-of-N key backup.
+#[cfg(feature = "std")]
+mod frost;
+
+// PASETO-style authenticated stateless tokens (`local`/`public`), built
+// on this module's AEAD and `frost`'s Schnorr signing.
+#[cfg(feature = "std")]
+mod token;
+
 // Conditional imports based on features
 #[cfg(feature = "std")]
 use rand::rngs::OsRng;
@@ -104,10 +158,139 @@ pub enum CryptoErrorCode {
     InternalError = -7,
     /// Hardware acceleration not available
     HardwareNotAvailable = -8,
+    /// The requested KDF/algorithm scheme tag is not recognized
+    UnsupportedScheme = -9,
+    /// The ciphertext container's format version is not supported
+    UnsupportedVersion = -10,
+    /// Fewer FROST signers participated than the key's threshold requires
+    InsufficientSigners = -11,
+    /// A FROST key share or signature share was rejected (wrong threshold,
+    /// missing/duplicate signer index, or a share that doesn't aggregate)
+    InvalidShare = -12,
+    /// A serialized Ristretto point was not a valid group element encoding
+    MalformedCommitment = -13,
+    /// A deserialized elliptic-curve point was the group identity element
+    InvalidIdentityElement = -14,
+}
+
+// errno-style introspection for the FFI boundary: every extern "C" entry
+// point funnels its return value through `fail`/`ok` below instead of a
+// bare `CryptoErrorCode as i32`, so `crusty_last_errno`/`crusty_strerror`
+// can tell a C caller what went wrong without it reimplementing the enum.
+//
+// `std` targets may call into this library from multiple threads, so the
+// slot is thread-local; embedded targets are single-core with no OS
+// scheduler, so a plain static with atomic access stands in for it there.
+#[cfg(feature = "std")]
+std::thread_local! {
+    static LAST_ERRNO: std::cell::Cell<i32> = std::cell::Cell::new(CryptoErrorCode::Success as i32);
+}
+
+#[cfg(all(not(feature = "std"), feature = "embedded"))]
+static LAST_ERRNO: core::sync::atomic::AtomicI32 =
+    core::sync::atomic::AtomicI32::new(CryptoErrorCode::Success as i32);
+
+fn set_last_errno(code: i32) {
+    #[cfg(feature = "std")]
+    LAST_ERRNO.with(|cell| cell.set(code));
+    #[cfg(all(not(feature = "std"), feature = "embedded"))]
+    LAST_ERRNO.store(code, core::sync::atomic::Ordering::Relaxed);
+    #[cfg(not(any(feature = "std", feature = "embedded")))]
+    let _ = code;
+}
+
+/// Records `code` as the last error on this thread (see
+/// [`crusty_last_errno`]) and returns it as the `i32` an FFI entry point
+/// hands back to C. Entry points should `return fail(CryptoErrorCode::X)`
+/// on every error path instead of a bare `CryptoErrorCode::X as i32`.
+fn fail(code: CryptoErrorCode) -> i32 {
+    set_last_errno(code as i32);
+    code as i32
+}
+
+/// Clears the last-error slot and returns `CryptoErrorCode::Success as
+/// i32`, for an FFI entry point's success path; see [`fail`].
+fn ok() -> i32 {
+    set_last_errno(CryptoErrorCode::Success as i32);
+    CryptoErrorCode::Success as i32
+}
+
+/// Returns the `CryptoErrorCode` (as `i32`) set by the most recently
+/// returning FFI call on this thread, or `Success` if none has run yet.
+/// Mirrors libc's `errno()`; lets a C/embedded caller diagnose a failure
+/// from `encrypt_data`/`decrypt_data`/etc. without maintaining its own
+/// copy of the `CryptoErrorCode` enum.
+#[no_mangle]
+pub extern "C" fn crusty_last_errno() -> i32 {
+    #[cfg(feature = "std")]
+    {
+        LAST_ERRNO.with(|cell| cell.get())
+    }
+    #[cfg(all(not(feature = "std"), feature = "embedded"))]
+    {
+        LAST_ERRNO.load(core::sync::atomic::Ordering::Relaxed)
+    }
+    #[cfg(not(any(feature = "std", feature = "embedded")))]
+    {
+        CryptoErrorCode::Success as i32
+    }
+}
+
+/// Overwrites the last-error slot, mirroring libc's `set_errno()`. Mainly
+/// useful for a caller that wants to reset it to `Success` between
+/// unrelated operations without making another FFI call that happens to
+/// succeed.
+#[no_mangle]
+pub extern "C" fn crusty_set_errno(code: i32) {
+    set_last_errno(code);
+}
+
+/// Returns a pointer to a stable, human-readable, NUL-terminated static
+/// string describing `code`, for a caller that wants to log/display
+/// `crusty_last_errno()`'s value. Mirrors libc's `strerror()`; never
+/// allocates, and the returned pointer is `'static` so it must not be
+/// freed. Unrecognized codes get a generic message rather than a null
+/// pointer.
+#[no_mangle]
+pub extern "C" fn crusty_strerror(code: i32) -> *const core::ffi::c_char {
+    let message: &[u8] = if code == CryptoErrorCode::Success as i32 {
+        b"success\0"
+    } else if code == CryptoErrorCode::InvalidParams as i32 {
+        b"invalid parameters\0"
+    } else if code == CryptoErrorCode::AuthenticationFailed as i32 {
+        b"authentication failed\0"
+    } else if code == CryptoErrorCode::EncryptionError as i32 {
+        b"encryption error\0"
+    } else if code == CryptoErrorCode::DecryptionError as i32 {
+        b"decryption error\0"
+    } else if code == CryptoErrorCode::KeyDerivationError as i32 {
+        b"key derivation error\0"
+    } else if code == CryptoErrorCode::BufferTooSmall as i32 {
+        b"buffer too small for output\0"
+    } else if code == CryptoErrorCode::InternalError as i32 {
+        b"internal error\0"
+    } else if code == CryptoErrorCode::HardwareNotAvailable as i32 {
+        b"hardware acceleration not available\0"
+    } else if code == CryptoErrorCode::UnsupportedScheme as i32 {
+        b"unsupported KDF/algorithm scheme\0"
+    } else if code == CryptoErrorCode::InsufficientSigners as i32 {
+        b"fewer FROST signers than the key's threshold\0"
+    } else if code == CryptoErrorCode::InvalidShare as i32 {
+        b"invalid FROST key share or signature share\0"
+    } else if code == CryptoErrorCode::MalformedCommitment as i32 {
+        b"malformed Ristretto point encoding\0"
+    } else if code == CryptoErrorCode::InvalidIdentityElement as i32 {
+        b"elliptic-curve point is the identity element\0"
+    } else if code == CryptoErrorCode::UnsupportedVersion as i32 {
+        b"unsupported container format version\0"
+    } else {
+        b"unknown error code\0"
+    };
+    message.as_ptr() as *const core::ffi::c_char
 }
 
 /// Encrypts data using AES-256-GCM with the provided password
-/// 
+///
 /// # Safety
 /// 
 /// This function is unsafe because it dereferences raw pointers.
@@ -125,130 +308,111 @@ pub unsafe extern "C" fn encrypt_data(
 ) -> i32 {
     // Validate parameters
     if data_ptr.is_null() || password_ptr.is_null() || output_ptr.is_null() || output_len.is_null() {
-        return CryptoErrorCode::InvalidParams as i32;
+        return fail(CryptoErrorCode::InvalidParams);
     }
     
     // Convert raw pointers to slices
     let data = core::slice::from_raw_parts(data_ptr, data_len);
     let password = core::slice::from_raw_parts(password_ptr, password_len);
     
-    // Try hardware acceleration first if available
-    #[cfg(feature = "embedded")]
-    {
-        if let Ok(result) = encrypt_with_hardware(data, password, output_ptr, output_max_len, output_len) {
-            return result;
-        }
-        // Fall back to software implementation if hardware acceleration fails
-    }
-    
-    // Software implementation
+    // Software implementation. Delegates to `encrypt_data_bytes`, which
+    // produces the versioned container (KDF salt included in the header),
+    // so this entry point and the `cxx` bridge can't diverge.
     #[cfg(feature = "std")]
     {
-        // Derive key from password
-        let key = match derive_key_from_password_internal(password) {
-            Ok(k) => k,
-            Err(_) => return CryptoErrorCode::KeyDerivationError as i32,
-        };
-        
-        // Generate a random nonce
-        let mut nonce_bytes = [0u8; 12];
-        OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
-        
-        // Create the cipher
-        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
-        
-        // Encrypt the data
-        let ciphertext = match cipher.encrypt(nonce, data) {
+        let container = match encrypt_data_bytes(data, password) {
             Ok(c) => c,
-            Err(_) => return CryptoErrorCode::EncryptionError as i32,
+            Err(_) => return fail(CryptoErrorCode::EncryptionError),
         };
-        
-        // Calculate required output size
-        let required_size = 12 + 4 + ciphertext.len(); // nonce + ciphertext length + ciphertext
-        
-        // Check if output buffer is large enough
-        if output_max_len < required_size {
-            *output_len = required_size;
-            return CryptoErrorCode::BufferTooSmall as i32;
+
+        if output_max_len < container.len() {
+            *output_len = container.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
         }
-        
-        // Write nonce to output
+
         let output_slice = core::slice::from_raw_parts_mut(output_ptr, output_max_len);
-        output_slice[0..12].copy_from_slice(&nonce_bytes);
-        
-        // Write ciphertext length to output
-        let ciphertext_len_bytes = (ciphertext.len() as u32).to_be_bytes();
-        output_slice[12..16].copy_from_slice(&ciphertext_len_bytes);
-        
-        // Write ciphertext to output
-        output_slice[16..16 + ciphertext.len()].copy_from_slice(&ciphertext);
-        
-        // Set output length
-        *output_len = required_size;
-        
-        return CryptoErrorCode::Success as i32;
+        output_slice[..container.len()].copy_from_slice(&container);
+        *output_len = container.len();
+
+        return ok();
     }
-    
-    // For embedded targets without std, if hardware acceleration failed
+
+    // For embedded targets without std
     #[cfg(all(not(feature = "std"), feature = "embedded"))]
     {
         // Simple key derivation for embedded targets
-        let key = match simple_key_derivation(password) {
+        let mut key = match simple_key_derivation(password) {
             Ok(k) => k,
-            Err(_) => return CryptoErrorCode::KeyDerivationError as i32,
+            Err(_) => return fail(CryptoErrorCode::KeyDerivationError),
         };
-        
+
         // Generate a random nonce using hardware RNG if available
         let mut nonce_bytes = [0u8; 12];
         if let Err(_) = get_random_bytes(&mut nonce_bytes) {
-            return CryptoErrorCode::InternalError as i32;
+            return fail(CryptoErrorCode::InternalError);
         }
         let nonce = Nonce::from_slice(&nonce_bytes);
-        
-        // Create the cipher
-        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
-        
-        // Encrypt the data
-        // For embedded targets, we use heapless::Vec to avoid dynamic allocation
+
+        // For embedded targets, we use heapless::Vec to avoid dynamic allocation.
         let mut ciphertext: Vec<u8, 2048> = Vec::new();
-        match cipher.encrypt_in_place_detached(nonce, data, &mut ciphertext) {
-            Ok(_tag) => {},
-            Err(_) => return CryptoErrorCode::EncryptionError as i32,
+
+        // Try the hardware accelerator first; fall back to software AES-GCM
+        // if none is compiled in or the peripheral can't initialize.
+        let mut hw_buf = [0u8; 2048 + 16];
+        let mut hw_written = 0usize;
+        match encrypt_with_hardware(&key, &nonce_bytes, data, hw_buf.as_mut_ptr(), hw_buf.len(), &mut hw_written) {
+            Ok(code) if code == CryptoErrorCode::Success as i32 => {
+                ciphertext.extend_from_slice(&hw_buf[..hw_written]).ok();
+                hw_buf.zeroize();
+            }
+            _ => {
+                // Create the cipher
+                let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+                match cipher.encrypt_in_place_detached(nonce, data, &mut ciphertext) {
+                    Ok(_tag) => {},
+                    Err(_) => {
+                        key.zeroize();
+                        return fail(CryptoErrorCode::EncryptionError);
+                    }
+                }
+            }
         }
-        
+        key.zeroize();
+
         // Calculate required output size
         let required_size = 12 + 4 + ciphertext.len(); // nonce + ciphertext length + ciphertext
-        
+
         // Check if output buffer is large enough
         if output_max_len < required_size {
             *output_len = required_size;
-            return CryptoErrorCode::BufferTooSmall as i32;
+            ciphertext.iter_mut().for_each(|b| *b = 0);
+            return fail(CryptoErrorCode::BufferTooSmall);
         }
-        
+
         // Write nonce to output
         let output_slice = core::slice::from_raw_parts_mut(output_ptr, output_max_len);
         output_slice[0..12].copy_from_slice(&nonce_bytes);
-        
+
         // Write ciphertext length to output
         let ciphertext_len_bytes = (ciphertext.len() as u32).to_be_bytes();
         output_slice[12..16].copy_from_slice(&ciphertext_len_bytes);
-        
+
         // Write ciphertext to output
         for (i, &byte) in ciphertext.iter().enumerate() {
             output_slice[16 + i] = byte;
         }
-        
+
         // Set output length
         *output_len = required_size;
-        
-        return CryptoErrorCode::Success as i32;
+        ciphertext.iter_mut().for_each(|b| *b = 0);
+
+        return ok();
     }
     
     // If we get here, neither std nor embedded features are enabled
     #[cfg(not(any(feature = "std", feature = "embedded")))]
     {
-        return CryptoErrorCode::InternalError as i32;
+        return fail(CryptoErrorCode::InternalError);
     }
 }
 
@@ -271,233 +435,1898 @@ pub unsafe extern "C" fn decrypt_data(
 ) -> i32 {
     // Validate parameters
     if data_ptr.is_null() || password_ptr.is_null() || output_ptr.is_null() || output_len.is_null() {
-        return CryptoErrorCode::InvalidParams as i32;
+        return fail(CryptoErrorCode::InvalidParams);
     }
     
-    // Check if data is long enough to contain nonce and length
+    // Check if data is long enough to contain at least a nonce and length
     if data_len < 16 {
-        return CryptoErrorCode::InvalidParams as i32;
+        return fail(CryptoErrorCode::InvalidParams);
     }
-    
+
     // Convert raw pointers to slices
     let data = core::slice::from_raw_parts(data_ptr, data_len);
     let password = core::slice::from_raw_parts(password_ptr, password_len);
-    
-    // Try hardware acceleration first if available
-    #[cfg(feature = "embedded")]
-    {
-        if let Ok(result) = decrypt_with_hardware(data, password, output_ptr, output_max_len, output_len) {
-            return result;
-        }
-        // Fall back to software implementation if hardware acceleration fails
-    }
-    
-    // Extract the nonce
-    let nonce = Nonce::from_slice(&data[0..12]);
-    
-    // Extract the ciphertext length
-    let ciphertext_len = u32::from_be_bytes([data[12], data[13], data[14], data[15]]) as usize;
-    
-    // Verify the data length
-    if data_len < 16 + ciphertext_len {
-        return CryptoErrorCode::InvalidParams as i32;
-    }
-    
-    // Extract the ciphertext
-    let ciphertext = &data[16..16 + ciphertext_len];
-    
-    // Software implementation
+
+    // Software implementation. Delegates to `decrypt_data_bytes`, which
+    // parses the versioned container header (and its embedded KDF salt)
+    // instead of assuming bare `nonce || len || ciphertext`.
     #[cfg(feature = "std")]
     {
-        // Derive key from password
-        let key = match derive_key_from_password_internal(password) {
-            Ok(k) => k,
-            Err(_) => return CryptoErrorCode::KeyDerivationError as i32,
-        };
-        
-        // Create the cipher
-        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
-        
-        // Decrypt the data
-        let plaintext = match cipher.decrypt(nonce, ciphertext) {
+        let mut plaintext = match decrypt_data_bytes(data, password) {
             Ok(p) => p,
-            Err(_) => return CryptoErrorCode::AuthenticationFailed as i32,
+            Err("unsupported container format version") => return fail(CryptoErrorCode::UnsupportedVersion),
+            Err(_) => return fail(CryptoErrorCode::AuthenticationFailed),
         };
-        
-        // Check if output buffer is large enough
+
         if output_max_len < plaintext.len() {
             *output_len = plaintext.len();
-            return CryptoErrorCode::BufferTooSmall as i32;
+            plaintext.zeroize();
+            return fail(CryptoErrorCode::BufferTooSmall);
         }
-        
-        // Write plaintext to output
+
         let output_slice = core::slice::from_raw_parts_mut(output_ptr, output_max_len);
         output_slice[0..plaintext.len()].copy_from_slice(&plaintext);
-        
-        // Set output length
         *output_len = plaintext.len();
-        
-        return CryptoErrorCode::Success as i32;
+        plaintext.zeroize();
+
+        return ok();
     }
-    
-    // For embedded targets without std, if hardware acceleration failed
+
+    // For embedded targets without std, the container header isn't used
+    // (no Argon2id, no salt to persist): the buffer is bare
+    // `nonce || ciphertext_len || ciphertext`.
     #[cfg(all(not(feature = "std"), feature = "embedded"))]
     {
+        let nonce_bytes: [u8; 12] = data[0..12].try_into().unwrap();
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let ciphertext_len = u32::from_be_bytes([data[12], data[13], data[14], data[15]]) as usize;
+        if data_len < 16 + ciphertext_len {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        let ciphertext = &data[16..16 + ciphertext_len];
+
         // Simple key derivation for embedded targets
-        let key = match simple_key_derivation(password) {
+        let mut key = match simple_key_derivation(password) {
             Ok(k) => k,
-            Err(_) => return CryptoErrorCode::KeyDerivationError as i32,
+            Err(_) => return fail(CryptoErrorCode::KeyDerivationError),
         };
-        
-        // Create the cipher
-        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
-        
-        // Decrypt the data
-        // For embedded targets, we use heapless::Vec to avoid dynamic allocation
+
+        // For embedded targets, we use heapless::Vec to avoid dynamic allocation.
         let mut plaintext: Vec<u8, 2048> = Vec::new();
-        match cipher.decrypt_in_place_detached(nonce, ciphertext, &mut plaintext) {
-            Ok(_) => {},
-            Err(_) => return CryptoErrorCode::AuthenticationFailed as i32,
+
+        // Try the hardware accelerator first; fall back to software AES-GCM
+        // if none is compiled in or the peripheral can't initialize.
+        let mut hw_buf = [0u8; 2048];
+        let mut hw_written = 0usize;
+        match decrypt_with_hardware(&key, &nonce_bytes, ciphertext, hw_buf.as_mut_ptr(), hw_buf.len(), &mut hw_written) {
+            Ok(code) if code == CryptoErrorCode::Success as i32 => {
+                plaintext.extend_from_slice(&hw_buf[..hw_written]).ok();
+                hw_buf.zeroize();
+            }
+            _ => {
+                // Create the cipher
+                let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+                match cipher.decrypt_in_place_detached(nonce, ciphertext, &mut plaintext) {
+                    Ok(_) => {},
+                    Err(_) => {
+                        key.zeroize();
+                        return fail(CryptoErrorCode::AuthenticationFailed);
+                    }
+                }
+            }
         }
-        
+        key.zeroize();
+
         // Check if output buffer is large enough
         if output_max_len < plaintext.len() {
             *output_len = plaintext.len();
-            return CryptoErrorCode::BufferTooSmall as i32;
+            plaintext.iter_mut().for_each(|b| *b = 0);
+            return fail(CryptoErrorCode::BufferTooSmall);
         }
-        
+
         // Write plaintext to output
         let output_slice = core::slice::from_raw_parts_mut(output_ptr, output_max_len);
         for (i, &byte) in plaintext.iter().enumerate() {
             output_slice[i] = byte;
         }
-        
+
         // Set output length
         *output_len = plaintext.len();
-        
-        return CryptoErrorCode::Success as i32;
+        plaintext.iter_mut().for_each(|b| *b = 0);
+
+        return ok();
     }
     
     // If we get here, neither std nor embedded features are enabled
     #[cfg(not(any(feature = "std", feature = "embedded")))]
     {
-        return CryptoErrorCode::InternalError as i32;
+        return fail(CryptoErrorCode::InternalError);
     }
 }
 
-// The following functions are only available with the std feature
+// Streaming chunked AEAD: unlike `encrypt_data`/`decrypt_data`, these don't
+// require the whole plaintext/ciphertext to fit in memory at once. Each
+// chunk is sealed/opened independently against a context created by
+// `stream_encrypt_init`/`stream_decrypt_init`. The std backend is
+// `streaming::{EncryptStream, DecryptStream}`; the embedded backend is
+// `embedded_features::{EmbeddedEncryptStream, EmbeddedDecryptStream}`. As
+// with `encrypt_data`/`decrypt_data`, one exported symbol per operation
+// serves both targets.
+
 #[cfg(feature = "std")]
-mod std_features {
-    use super::*;
-    
-    /// Hashes a password using Argon2id for verification
-    /// 
-    /// # Safety
-    /// 
-    /// This function is unsafe because it dereferences raw pointers.
-    /// The caller must ensure that:
-    /// - `password_ptr` points to a valid buffer of at least `password_len` bytes
-    /// - `output_ptr` points to a buffer of at least `output_len` bytes
-    #[no_mangle]
-    pub unsafe extern "C" fn hash_password(
-        password_ptr: *const u8, password_len: usize,
-        output_ptr: *mut u8, output_len: usize
-    ) -> i32 {
-        // Validate parameters
-        if password_ptr.is_null() || output_ptr.is_null() {
-            return CryptoErrorCode::InvalidParams as i32;
+type EncryptStreamHandle = streaming::EncryptStream;
+#[cfg(feature = "std")]
+type DecryptStreamHandle = streaming::DecryptStream;
+#[cfg(all(not(feature = "std"), feature = "embedded"))]
+type EncryptStreamHandle = embedded_features::EmbeddedEncryptStream;
+#[cfg(all(not(feature = "std"), feature = "embedded"))]
+type DecryptStreamHandle = embedded_features::EmbeddedDecryptStream;
+
+/// Starts a streaming AES-256-GCM encryption session keyed from
+/// `password`. On std, derives the key at Argon2id cost `params` (or
+/// `Argon2Params::default_cost()` if `params` is null) with a freshly
+/// generated 16-byte salt written to `salt_out`; on embedded, uses the
+/// same placeholder key derivation as `encrypt_data` and leaves
+/// `salt_out` untouched. Either way, a fresh 4-byte random stream prefix
+/// is written to `stream_prefix_out`. The caller must persist whichever
+/// of these outputs apply (e.g. in a file header) so `stream_decrypt_init`
+/// can reconstruct the same key and nonce sequence. On success,
+/// `*ctx_out` receives an opaque context that must eventually be
+/// consumed by exactly one of `stream_encrypt_final` or
+/// `stream_encrypt_abort`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers. The
+/// caller must ensure that:
+/// - `password_ptr` points to a valid buffer of at least `password_len` bytes
+/// - `params`, if non-null, points to a valid `Argon2Params` (std only)
+/// - `salt_out` points to a buffer of at least 16 bytes (std only)
+/// - `stream_prefix_out` points to a buffer of at least 4 bytes
+/// - `ctx_out` points to a valid `*mut EncryptStreamHandle`
+#[no_mangle]
+pub unsafe extern "C" fn stream_encrypt_init(
+    password_ptr: *const u8, password_len: usize,
+    params: *const Argon2Params,
+    salt_out: *mut u8,
+    stream_prefix_out: *mut u8,
+    ctx_out: *mut *mut EncryptStreamHandle
+) -> i32 {
+    if password_ptr.is_null() || stream_prefix_out.is_null() || ctx_out.is_null() {
+        return fail(CryptoErrorCode::InvalidParams);
+    }
+
+    let password = core::slice::from_raw_parts(password_ptr, password_len);
+
+    #[cfg(feature = "std")]
+    {
+        if salt_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
         }
-        
-        // Convert raw pointers to slices
-        let password = std::slice::from_raw_parts(password_ptr, password_len);
-        
-        // Generate a salt
-        let salt = SaltString::generate(&mut Argon2OsRng);
-        
-        // Create Argon2id instance
-        let argon2 = Argon2::default();
-        
-        // Hash the password
-        let password_hash = match argon2.hash_password(password, &salt) {
-            Ok(hash) => hash.to_string(),
-            Err(_) => return CryptoErrorCode::KeyDerivationError as i32,
+
+        let argon2_params = if params.is_null() { Argon2Params::default_cost() } else { *params };
+
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        let key = match std_features::derive_key_from_password_internal_with_params(password, &salt, argon2_params) {
+            Ok(key) => key,
+            Err("parallelism must be nonzero") | Err("memory_kib below the minimum recommended cost") => {
+                return fail(CryptoErrorCode::InvalidParams);
+            }
+            Err(_) => return fail(CryptoErrorCode::KeyDerivationError),
         };
-        
-        // Check if output buffer is large enough
-        if output_len < password_hash.len() {
-            return CryptoErrorCode::BufferTooSmall as i32;
-        }
-        
-        // Write hash to output
-        let output_slice = std::slice::from_raw_parts_mut(output_ptr, output_len);
-        output_slice[0..password_hash.len()].copy_from_slice(password_hash.as_bytes());
-        
-        // Null-terminate the string
-        if output_len > password_hash.len() {
-            output_slice[password_hash.len()] = 0;
-        }
-        
-        CryptoErrorCode::Success as i32
+
+        let stream = streaming::EncryptStream::new(key);
+        core::slice::from_raw_parts_mut(salt_out, 16).copy_from_slice(&salt);
+        core::slice::from_raw_parts_mut(stream_prefix_out, 4).copy_from_slice(&stream.stream_prefix());
+        *ctx_out = std::boxed::Box::into_raw(std::boxed::Box::new(stream));
+        return ok();
     }
 
-    /// Derives an encryption key from a password and salt
-    /// 
-    /// # Safety
-    /// 
-    /// This function is unsafe because it dereferences raw pointers.
-    /// The caller must ensure that:
-    /// - `password_ptr` points to a valid buffer of at least `password_len` bytes
-    /// - `salt_ptr` points to a valid buffer of at least `salt_len` bytes
-    /// - `key_ptr` points to a buffer of at least `key_len` bytes
-    #[no_mangle]
-    pub unsafe extern "C" fn derive_key_from_password(
-        password_ptr: *const u8, password_len: usize,
-        salt_ptr: *const u8, salt_len: usize,
-        key_ptr: *mut u8, key_len: usize
-    ) -> i32 {
-        // Validate parameters
-        if password_ptr.is_null() || salt_ptr.is_null() || key_ptr.is_null() {
-            return CryptoErrorCode::InvalidParams as i32;
+    #[cfg(all(not(feature = "std"), feature = "embedded"))]
+    {
+        let key = match embedded_features::simple_key_derivation(password) {
+            Ok(k) => k,
+            Err(_) => return fail(CryptoErrorCode::KeyDerivationError),
+        };
+
+        let mut stream_prefix = [0u8; 4];
+        if embedded_features::get_random_bytes(&mut stream_prefix).is_err() {
+            return fail(CryptoErrorCode::InternalError);
         }
-        
-        // Check if key length is valid
-        if key_len != 32 {
-            return CryptoErrorCode::InvalidParams as i32;
+
+        let stream = embedded_features::EmbeddedEncryptStream::new(key, stream_prefix);
+        core::slice::from_raw_parts_mut(stream_prefix_out, 4).copy_from_slice(&stream_prefix);
+        *ctx_out = alloc::boxed::Box::into_raw(alloc::boxed::Box::new(stream));
+        return ok();
+    }
+
+    #[cfg(not(any(feature = "std", feature = "embedded")))]
+    {
+        return fail(CryptoErrorCode::InternalError);
+    }
+}
+
+/// Seals one chunk of an in-progress stream started by
+/// `stream_encrypt_init`. Chunks must be decrypted in the same order they
+/// were sealed; this is for all but the last chunk of the plaintext --
+/// use `stream_encrypt_final` for that one.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers. The
+/// caller must ensure that:
+/// - `ctx` is a valid, non-null context from `stream_encrypt_init` that
+///   hasn't already been consumed by `stream_encrypt_final`/`stream_encrypt_abort`
+/// - `chunk_ptr` points to a valid buffer of at least `chunk_len` bytes
+/// - `output_ptr` points to a buffer of at least `output_max_len` bytes
+/// - `output_len` points to a valid `usize`
+#[no_mangle]
+pub unsafe extern "C" fn stream_encrypt_update(
+    ctx: *mut EncryptStreamHandle,
+    chunk_ptr: *const u8, chunk_len: usize,
+    output_ptr: *mut u8, output_max_len: usize,
+    output_len: *mut usize
+) -> i32 {
+    stream_encrypt_seal(ctx, chunk_ptr, chunk_len, output_ptr, output_max_len, output_len, false)
+}
+
+/// Seals the last chunk of an in-progress stream and consumes `ctx` --
+/// whether this succeeds or fails, `ctx` must not be used again
+/// afterwards. The final chunk may be empty, for plaintexts whose length
+/// is an exact multiple of the chunk size.
+///
+/// # Safety
+///
+/// Same requirements as `stream_encrypt_update`, except `ctx` is consumed
+/// by this call.
+#[no_mangle]
+pub unsafe extern "C" fn stream_encrypt_final(
+    ctx: *mut EncryptStreamHandle,
+    chunk_ptr: *const u8, chunk_len: usize,
+    output_ptr: *mut u8, output_max_len: usize,
+    output_len: *mut usize
+) -> i32 {
+    stream_encrypt_seal(ctx, chunk_ptr, chunk_len, output_ptr, output_max_len, output_len, true)
+}
+
+unsafe fn stream_encrypt_seal(
+    ctx: *mut EncryptStreamHandle,
+    chunk_ptr: *const u8, chunk_len: usize,
+    output_ptr: *mut u8, output_max_len: usize,
+    output_len: *mut usize,
+    is_final: bool
+) -> i32 {
+    if ctx.is_null() || (chunk_ptr.is_null() && chunk_len != 0) || output_ptr.is_null() || output_len.is_null() {
+        return fail(CryptoErrorCode::InvalidParams);
+    }
+
+    // `from_raw_parts` requires a non-null pointer even for a zero-length
+    // slice, so the empty final chunk can't just fall through to it below.
+    let chunk: &[u8] = if chunk_len == 0 { &[] } else { core::slice::from_raw_parts(chunk_ptr, chunk_len) };
+
+    #[cfg(any(feature = "std", feature = "embedded"))]
+    {
+        let stream = &mut *ctx;
+        let result = if is_final { stream.finish(chunk) } else { stream.update(chunk) };
+
+        // On a failed `update`, `ctx` stays live for `stream_encrypt_abort`;
+        // only `final` consumes it regardless of outcome.
+        #[cfg(feature = "std")]
+        let sealed = match result {
+            Ok(sealed) => sealed,
+            Err(_) => {
+                if is_final {
+                    drop(std::boxed::Box::from_raw(ctx));
+                }
+                return fail(CryptoErrorCode::EncryptionError);
+            }
+        };
+        #[cfg(all(not(feature = "std"), feature = "embedded"))]
+        let sealed = match result {
+            Ok(sealed) => sealed,
+            Err(code) => {
+                if is_final {
+                    drop(alloc::boxed::Box::from_raw(ctx));
+                }
+                return fail(code);
+            }
+        };
+
+        if is_final {
+            #[cfg(feature = "std")]
+            drop(std::boxed::Box::from_raw(ctx));
+            #[cfg(all(not(feature = "std"), feature = "embedded"))]
+            drop(alloc::boxed::Box::from_raw(ctx));
+        }
+
+        if output_max_len < sealed.len() {
+            *output_len = sealed.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+        core::slice::from_raw_parts_mut(output_ptr, output_max_len)[..sealed.len()].copy_from_slice(&sealed);
+        *output_len = sealed.len();
+        return ok();
+    }
+
+    #[cfg(not(any(feature = "std", feature = "embedded")))]
+    {
+        return fail(CryptoErrorCode::InternalError);
+    }
+}
+
+/// Abandons an in-progress encryption stream without sealing a final
+/// chunk, freeing `ctx`. Use this to clean up after an error (e.g. the
+/// underlying file write failed) instead of leaking the context.
+///
+/// # Safety
+///
+/// `ctx` must be a valid, non-null context from `stream_encrypt_init`
+/// that hasn't already been consumed, and must not be used again after
+/// this call.
+#[no_mangle]
+pub unsafe extern "C" fn stream_encrypt_abort(ctx: *mut EncryptStreamHandle) {
+    if ctx.is_null() {
+        return;
+    }
+    #[cfg(feature = "std")]
+    drop(std::boxed::Box::from_raw(ctx));
+    #[cfg(all(not(feature = "std"), feature = "embedded"))]
+    drop(alloc::boxed::Box::from_raw(ctx));
+}
+
+/// Starts a streaming AES-256-GCM decryption session matching a stream
+/// `stream_encrypt_init` began: `salt` (std only, ignored on embedded)
+/// and `stream_prefix` must be whatever the encrypt side wrote out, and
+/// `params` (std only) must match whatever cost the encrypt side used (or
+/// be null for `Argon2Params::default_cost()`, which is also the default
+/// on the encrypt side). On success, `*ctx_out` receives an opaque
+/// context that must eventually be consumed by exactly one of
+/// `stream_decrypt_final` or `stream_decrypt_abort`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers. The
+/// caller must ensure that:
+/// - `password_ptr` points to a valid buffer of at least `password_len` bytes
+/// - `params`, if non-null, points to a valid `Argon2Params` (std only)
+/// - `salt_ptr` points to a valid buffer of at least 16 bytes (std only)
+/// - `stream_prefix_ptr` points to a valid buffer of at least 4 bytes
+/// - `ctx_out` points to a valid `*mut DecryptStreamHandle`
+#[no_mangle]
+pub unsafe extern "C" fn stream_decrypt_init(
+    password_ptr: *const u8, password_len: usize,
+    params: *const Argon2Params,
+    salt_ptr: *const u8,
+    stream_prefix_ptr: *const u8,
+    ctx_out: *mut *mut DecryptStreamHandle
+) -> i32 {
+    if password_ptr.is_null() || stream_prefix_ptr.is_null() || ctx_out.is_null() {
+        return fail(CryptoErrorCode::InvalidParams);
+    }
+
+    let password = core::slice::from_raw_parts(password_ptr, password_len);
+    let stream_prefix: [u8; 4] = match core::slice::from_raw_parts(stream_prefix_ptr, 4).try_into() {
+        Ok(bytes) => bytes,
+        Err(_) => return fail(CryptoErrorCode::InvalidParams),
+    };
+
+    #[cfg(feature = "std")]
+    {
+        if salt_ptr.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        let salt = core::slice::from_raw_parts(salt_ptr, 16);
+        let argon2_params = if params.is_null() { Argon2Params::default_cost() } else { *params };
+
+        let key = match std_features::derive_key_from_password_internal_with_params(password, salt, argon2_params) {
+            Ok(key) => key,
+            Err("parallelism must be nonzero") | Err("memory_kib below the minimum recommended cost") => {
+                return fail(CryptoErrorCode::InvalidParams);
+            }
+            Err(_) => return fail(CryptoErrorCode::KeyDerivationError),
+        };
+
+        let stream = streaming::DecryptStream::new(key, stream_prefix);
+        *ctx_out = std::boxed::Box::into_raw(std::boxed::Box::new(stream));
+        return ok();
+    }
+
+    #[cfg(all(not(feature = "std"), feature = "embedded"))]
+    {
+        let key = match embedded_features::simple_key_derivation(password) {
+            Ok(k) => k,
+            Err(_) => return fail(CryptoErrorCode::KeyDerivationError),
+        };
+
+        let stream = embedded_features::EmbeddedDecryptStream::new(key, stream_prefix);
+        *ctx_out = alloc::boxed::Box::into_raw(alloc::boxed::Box::new(stream));
+        return ok();
+    }
+
+    #[cfg(not(any(feature = "std", feature = "embedded")))]
+    {
+        return fail(CryptoErrorCode::InternalError);
+    }
+}
+
+/// Opens one chunk of an in-progress stream started by
+/// `stream_decrypt_init`, matching a chunk sealed by
+/// `stream_encrypt_update`. Chunks must be decrypted in the same order
+/// they were sealed.
+///
+/// # Safety
+///
+/// Same requirements as `stream_encrypt_update`, for the decrypt context.
+#[no_mangle]
+pub unsafe extern "C" fn stream_decrypt_update(
+    ctx: *mut DecryptStreamHandle,
+    chunk_ptr: *const u8, chunk_len: usize,
+    output_ptr: *mut u8, output_max_len: usize,
+    output_len: *mut usize
+) -> i32 {
+    stream_decrypt_open(ctx, chunk_ptr, chunk_len, output_ptr, output_max_len, output_len, false)
+}
+
+/// Opens the last chunk of an in-progress stream, matching a chunk sealed
+/// by `stream_encrypt_final`, and consumes `ctx`. Authentication fails
+/// here if the stream was truncated -- i.e. a chunk sealed with
+/// `stream_encrypt_update` is opened as if it were the final one.
+///
+/// # Safety
+///
+/// Same requirements as `stream_decrypt_update`, except `ctx` is consumed
+/// by this call.
+#[no_mangle]
+pub unsafe extern "C" fn stream_decrypt_final(
+    ctx: *mut DecryptStreamHandle,
+    chunk_ptr: *const u8, chunk_len: usize,
+    output_ptr: *mut u8, output_max_len: usize,
+    output_len: *mut usize
+) -> i32 {
+    stream_decrypt_open(ctx, chunk_ptr, chunk_len, output_ptr, output_max_len, output_len, true)
+}
+
+unsafe fn stream_decrypt_open(
+    ctx: *mut DecryptStreamHandle,
+    chunk_ptr: *const u8, chunk_len: usize,
+    output_ptr: *mut u8, output_max_len: usize,
+    output_len: *mut usize,
+    is_final: bool
+) -> i32 {
+    if ctx.is_null() || (chunk_ptr.is_null() && chunk_len != 0) || output_ptr.is_null() || output_len.is_null() {
+        return fail(CryptoErrorCode::InvalidParams);
+    }
+
+    // `from_raw_parts` requires a non-null pointer even for a zero-length
+    // slice, so the empty final chunk can't just fall through to it below.
+    let chunk: &[u8] = if chunk_len == 0 { &[] } else { core::slice::from_raw_parts(chunk_ptr, chunk_len) };
+
+    #[cfg(any(feature = "std", feature = "embedded"))]
+    {
+        let stream = &mut *ctx;
+        let result = if is_final { stream.finish(chunk) } else { stream.update(chunk) };
+
+        // On a failed `update`, `ctx` stays live for `stream_decrypt_abort`;
+        // only `final` consumes it regardless of outcome.
+        #[cfg(feature = "std")]
+        let mut plaintext = match result {
+            Ok(plaintext) => plaintext,
+            Err(_) => {
+                if is_final {
+                    drop(std::boxed::Box::from_raw(ctx));
+                }
+                return fail(CryptoErrorCode::AuthenticationFailed);
+            }
+        };
+        #[cfg(all(not(feature = "std"), feature = "embedded"))]
+        let mut plaintext = match result {
+            Ok(plaintext) => plaintext,
+            Err(code) => {
+                if is_final {
+                    drop(alloc::boxed::Box::from_raw(ctx));
+                }
+                return fail(code);
+            }
+        };
+
+        if is_final {
+            #[cfg(feature = "std")]
+            drop(std::boxed::Box::from_raw(ctx));
+            #[cfg(all(not(feature = "std"), feature = "embedded"))]
+            drop(alloc::boxed::Box::from_raw(ctx));
+        }
+
+        if output_max_len < plaintext.len() {
+            *output_len = plaintext.len();
+            plaintext.iter_mut().for_each(|b| *b = 0);
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+        core::slice::from_raw_parts_mut(output_ptr, output_max_len)[..plaintext.len()].copy_from_slice(&plaintext);
+        *output_len = plaintext.len();
+        // The caller's buffer now has its own copy; scrub ours instead of
+        // leaving the plaintext sitting in this dropped Vec's backing
+        // storage (heap on std, stack on embedded).
+        plaintext.iter_mut().for_each(|b| *b = 0);
+        return ok();
+    }
+
+    #[cfg(not(any(feature = "std", feature = "embedded")))]
+    {
+        return fail(CryptoErrorCode::InternalError);
+    }
+}
+
+/// Abandons an in-progress decryption stream, freeing `ctx`; see
+/// `stream_encrypt_abort`.
+///
+/// # Safety
+///
+/// `ctx` must be a valid, non-null context from `stream_decrypt_init`
+/// that hasn't already been consumed, and must not be used again after
+/// this call.
+#[no_mangle]
+pub unsafe extern "C" fn stream_decrypt_abort(ctx: *mut DecryptStreamHandle) {
+    if ctx.is_null() {
+        return;
+    }
+    #[cfg(feature = "std")]
+    drop(std::boxed::Box::from_raw(ctx));
+    #[cfg(all(not(feature = "std"), feature = "embedded"))]
+    drop(alloc::boxed::Box::from_raw(ctx));
+}
+
+// The following functions are only available with the std feature
+#[cfg(feature = "std")]
+mod std_features {
+    use super::*;
+
+    /// Hashes a password using Argon2id for verification
+    /// 
+    /// # Safety
+    /// 
+    /// This function is unsafe because it dereferences raw pointers.
+    /// The caller must ensure that:
+    /// - `password_ptr` points to a valid buffer of at least `password_len` bytes
+    /// - `output_ptr` points to a buffer of at least `output_len` bytes
+    #[no_mangle]
+    pub unsafe extern "C" fn hash_password(
+        password_ptr: *const u8, password_len: usize,
+        output_ptr: *mut u8, output_len: usize
+    ) -> i32 {
+        // Validate parameters
+        if password_ptr.is_null() || output_ptr.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        
+        // Convert raw pointers to slices
+        let password = std::slice::from_raw_parts(password_ptr, password_len);
+        
+        // Generate a salt
+        let salt = SaltString::generate(&mut Argon2OsRng);
+        
+        // Create Argon2id instance
+        let argon2 = Argon2::default();
+        
+        // Hash the password
+        let password_hash = match argon2.hash_password(password, &salt) {
+            Ok(hash) => hash.to_string(),
+            Err(_) => return fail(CryptoErrorCode::KeyDerivationError),
+        };
+        
+        // Check if output buffer is large enough
+        if output_len < password_hash.len() {
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+        
+        // Write hash to output
+        let output_slice = std::slice::from_raw_parts_mut(output_ptr, output_len);
+        output_slice[0..password_hash.len()].copy_from_slice(password_hash.as_bytes());
+        
+        // Null-terminate the string
+        if output_len > password_hash.len() {
+            output_slice[password_hash.len()] = 0;
+        }
+        
+        ok()
+    }
+
+    /// Derives an encryption key from a password and salt
+    /// 
+    /// # Safety
+    /// 
+    /// This function is unsafe because it dereferences raw pointers.
+    /// The caller must ensure that:
+    /// - `password_ptr` points to a valid buffer of at least `password_len` bytes
+    /// - `salt_ptr` points to a valid buffer of at least `salt_len` bytes
+    /// - `key_ptr` points to a buffer of at least `key_len` bytes
+    #[no_mangle]
+    pub unsafe extern "C" fn derive_key_from_password(
+        password_ptr: *const u8, password_len: usize,
+        salt_ptr: *const u8, salt_len: usize,
+        key_ptr: *mut u8, key_len: usize
+    ) -> i32 {
+        // Validate parameters
+        if password_ptr.is_null() || salt_ptr.is_null() || key_ptr.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        
+        // Check if key length is valid
+        if key_len != 32 {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        
+        // Convert raw pointers to slices
+        let password = std::slice::from_raw_parts(password_ptr, password_len);
+        let salt = std::slice::from_raw_parts(salt_ptr, salt_len);
+        
+        // Create Argon2id instance
+        let argon2 = Argon2::default();
+        
+        // Derive key
+        let mut key = [0u8; 32];
+        if let Err(_) = argon2.hash_password_into(password, salt, &mut key) {
+            return fail(CryptoErrorCode::KeyDerivationError);
+        }
+
+        // Write key to output
+        let key_slice = std::slice::from_raw_parts_mut(key_ptr, key_len);
+        key_slice.copy_from_slice(&key);
+        key.zeroize();
+
+        ok()
+    }
+
+    /// Argon2id KDF tag stored in the container header's `kdf_id` byte.
+    pub(crate) const KDF_ID_ARGON2ID: u8 = 0;
+    /// AES-256-GCM algorithm tag stored in the container header's
+    /// `algorithm_id` byte.
+    pub(crate) const ALGORITHM_ID_AES_256_GCM: u8 = 0;
+    /// AES-256-GCM-SIV algorithm tag. Nonce-misuse-resistant: this FFI
+    /// generates nonces with a placeholder PRNG on embedded targets and
+    /// with `OsRng` on PC, so a repeated nonce under plain GCM would be
+    /// catastrophic; GCM-SIV degrades gracefully to only leaking
+    /// plaintext equality in that case.
+    pub(crate) const ALGORITHM_ID_AES_256_GCM_SIV: u8 = 1;
+
+    /// Encrypts `data` under a key derived from `password` using the
+    /// default algorithm (AES-256-GCM). See [`encrypt_data_bytes_ex`] to
+    /// select a different one.
+    pub(crate) fn encrypt_data_bytes(data: &[u8], password: &[u8]) -> Result<std::vec::Vec<u8>, &'static str> {
+        encrypt_data_bytes_ex(data, password, ALGORITHM_ID_AES_256_GCM)
+    }
+
+    /// Like [`encrypt_data_bytes`], but lets the caller pick the AEAD
+    /// algorithm; uses `Argon2::default()`'s cost. See
+    /// [`encrypt_data_bytes_with_params`] to tune it.
+    pub(crate) fn encrypt_data_bytes_ex(data: &[u8], password: &[u8], algorithm_id: u8) -> Result<std::vec::Vec<u8>, &'static str> {
+        encrypt_data_bytes_with_params(data, password, algorithm_id, crate::kdf::Argon2Params::default_cost())
+    }
+
+    /// Encrypts `data` under a key derived from `password` with the given
+    /// Argon2 cost parameters, and returns a versioned container: a
+    /// header (carrying a freshly generated KDF salt, the chosen
+    /// `algorithm_id`, and the Argon2 parameters) followed by
+    /// `nonce || ciphertext_len || ciphertext`.
+    ///
+    /// This is the logic shared by the `extern "C"` `encrypt_data`/
+    /// `encrypt_data_ex`/`encrypt_data_with_params` entry points (which
+    /// write into a caller-provided buffer) and the `cxx` bridge, so the
+    /// FFI surfaces can't diverge.
+    pub(crate) fn encrypt_data_bytes_with_params(
+        data: &[u8],
+        password: &[u8],
+        algorithm_id: u8,
+        argon2_params: crate::kdf::Argon2Params,
+    ) -> Result<std::vec::Vec<u8>, &'static str> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let mut key = derive_key_from_password_internal_with_params(password, &salt, argon2_params)?;
+
+        let mut nonce_bytes = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce_bytes);
+
+        let ciphertext = match algorithm_id {
+            ALGORITHM_ID_AES_256_GCM => {
+                let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+                cipher.encrypt(Nonce::from_slice(&nonce_bytes), data).map_err(|_| "encryption failed")?
+            }
+            ALGORITHM_ID_AES_256_GCM_SIV => {
+                use aes_gcm_siv::aead::{Aead as SivAead, KeyInit as SivKeyInit};
+                let cipher = Aes256GcmSiv::new(aes_gcm_siv::Key::<Aes256GcmSiv>::from_slice(&key));
+                cipher
+                    .encrypt(aes_gcm_siv::Nonce::from_slice(&nonce_bytes), data)
+                    .map_err(|_| "encryption failed")?
+            }
+            _ => return Err("unsupported algorithm"),
+        };
+        key.zeroize();
+
+        let mut out = crate::container::encode_header(algorithm_id, KDF_ID_ARGON2ID, &salt, argon2_params);
+        out.extend_from_slice(&nonce_bytes);
+        out.extend_from_slice(&(ciphertext.len() as u32).to_be_bytes());
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    /// Reverses [`encrypt_data_bytes`]/[`encrypt_data_bytes_ex`]/
+    /// [`encrypt_data_bytes_with_params`]; shared by `decrypt_data` and
+    /// the `cxx` bridge. Reads the salt, `algorithm_id`, and Argon2
+    /// parameters back out of the container header, so the exact key and
+    /// cipher used at encrypt time are reconstructed regardless of which
+    /// entry point produced the container.
+    pub(crate) fn decrypt_data_bytes(data: &[u8], password: &[u8]) -> Result<std::vec::Vec<u8>, &'static str> {
+        let header = crate::container::decode_header(data)?;
+        let body = &data[header.body_offset..];
+
+        if body.len() < 16 {
+            return Err("buffer too short to contain a nonce and length prefix");
+        }
+
+        let nonce_bytes = &body[0..12];
+        let ciphertext_len = u32::from_be_bytes([body[12], body[13], body[14], body[15]]) as usize;
+        if body.len() < 16 + ciphertext_len {
+            return Err("buffer too short for the declared ciphertext length");
+        }
+        let ciphertext = &body[16..16 + ciphertext_len];
+
+        let mut key = derive_key_from_password_internal_with_params(password, &header.salt, header.argon2_params)?;
+
+        let plaintext = match header.algorithm_id {
+            ALGORITHM_ID_AES_256_GCM => {
+                let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+                cipher.decrypt(Nonce::from_slice(nonce_bytes), ciphertext).map_err(|_| "authentication failed")
+            }
+            ALGORITHM_ID_AES_256_GCM_SIV => {
+                use aes_gcm_siv::aead::{Aead as SivAead, KeyInit as SivKeyInit};
+                let cipher = Aes256GcmSiv::new(aes_gcm_siv::Key::<Aes256GcmSiv>::from_slice(&key));
+                cipher
+                    .decrypt(aes_gcm_siv::Nonce::from_slice(nonce_bytes), ciphertext)
+                    .map_err(|_| "authentication failed")
+            }
+            _ => Err("unsupported algorithm"),
+        };
+        key.zeroize();
+        plaintext
+    }
+
+    /// Encrypts `data` like [`encrypt_data`], but lets the caller pick the
+    /// AEAD algorithm (see `ALGORITHM_ID_AES_256_GCM`/`_SIV`) instead of
+    /// always using AES-256-GCM. The choice is recorded in the container
+    /// header, so the matching `decrypt_data` call needs no extra
+    /// parameter -- it dispatches on the stored `algorithm_id`.
+    ///
+    /// # Safety
+    ///
+    /// Same pointer/length requirements as [`encrypt_data`].
+    #[no_mangle]
+    pub unsafe extern "C" fn encrypt_data_ex(
+        data_ptr: *const u8, data_len: usize,
+        password_ptr: *const u8, password_len: usize,
+        algorithm_id: u8,
+        output_ptr: *mut u8, output_max_len: usize,
+        output_len: *mut usize
+    ) -> i32 {
+        if data_ptr.is_null() || password_ptr.is_null() || output_ptr.is_null() || output_len.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let data = std::slice::from_raw_parts(data_ptr, data_len);
+        let password = std::slice::from_raw_parts(password_ptr, password_len);
+
+        let container = match encrypt_data_bytes_ex(data, password, algorithm_id) {
+            Ok(c) => c,
+            Err("unsupported algorithm") => return fail(CryptoErrorCode::UnsupportedScheme),
+            Err(_) => return fail(CryptoErrorCode::EncryptionError),
+        };
+
+        if output_max_len < container.len() {
+            *output_len = container.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+
+        let output_slice = std::slice::from_raw_parts_mut(output_ptr, output_max_len);
+        output_slice[..container.len()].copy_from_slice(&container);
+        *output_len = container.len();
+
+        ok()
+    }
+
+    /// Encrypts `data` like [`encrypt_data_ex`], but also lets the caller
+    /// tune Argon2id's own cost via `params` instead of
+    /// `Argon2::default()`. The parameters are recorded in the container
+    /// header, so decryption reconstructs the exact same cost settings
+    /// without the caller needing to resupply them.
+    ///
+    /// # Safety
+    ///
+    /// Same pointer/length requirements as [`encrypt_data`]. `params` must
+    /// point to a valid [`crate::kdf::Argon2Params`].
+    #[no_mangle]
+    pub unsafe extern "C" fn encrypt_data_with_params(
+        data_ptr: *const u8, data_len: usize,
+        password_ptr: *const u8, password_len: usize,
+        algorithm_id: u8,
+        params: *const crate::kdf::Argon2Params,
+        output_ptr: *mut u8, output_max_len: usize,
+        output_len: *mut usize
+    ) -> i32 {
+        if data_ptr.is_null() || password_ptr.is_null() || params.is_null() || output_ptr.is_null() || output_len.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let data = std::slice::from_raw_parts(data_ptr, data_len);
+        let password = std::slice::from_raw_parts(password_ptr, password_len);
+
+        let container = match encrypt_data_bytes_with_params(data, password, algorithm_id, *params) {
+            Ok(c) => c,
+            Err("unsupported algorithm") => return fail(CryptoErrorCode::UnsupportedScheme),
+            Err("parallelism must be nonzero") | Err("memory_kib below the minimum recommended cost") => {
+                return fail(CryptoErrorCode::InvalidParams);
+            }
+            Err(_) => return fail(CryptoErrorCode::EncryptionError),
+        };
+
+        if output_max_len < container.len() {
+            *output_len = container.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+
+        let output_slice = std::slice::from_raw_parts_mut(output_ptr, output_max_len);
+        output_slice[..container.len()].copy_from_slice(&container);
+        *output_len = container.len();
+
+        ok()
+    }
+
+    /// Hashes a password with a caller-selected [`KdfScheme`] instead of
+    /// the Argon2id default used by [`hash_password`].
+    ///
+    /// # Safety
+    ///
+    /// Same pointer/length requirements as [`hash_password`].
+    #[no_mangle]
+    pub unsafe extern "C" fn hash_password_ex(
+        password_ptr: *const u8, password_len: usize,
+        scheme: u8,
+        output_ptr: *mut u8, output_len: usize
+    ) -> i32 {
+        if password_ptr.is_null() || output_ptr.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let Some(scheme) = crate::KdfScheme::from_u8(scheme) else {
+            return fail(CryptoErrorCode::UnsupportedScheme);
+        };
+
+        let password = std::slice::from_raw_parts(password_ptr, password_len);
+
+        let hash = match scheme {
+            crate::KdfScheme::Argon2id => {
+                let salt = SaltString::generate(&mut Argon2OsRng);
+                match Argon2::default().hash_password(password, &salt) {
+                    Ok(hash) => hash.to_string(),
+                    Err(_) => return fail(CryptoErrorCode::KeyDerivationError),
+                }
+            }
+            crate::KdfScheme::Pbkdf2HmacSha512 => {
+                crate::kdf::hash_password_pbkdf2(password, crate::kdf::DEFAULT_PBKDF2_ITERATIONS)
+            }
+        };
+
+        if output_len < hash.len() {
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+
+        let output_slice = std::slice::from_raw_parts_mut(output_ptr, output_len);
+        output_slice[0..hash.len()].copy_from_slice(hash.as_bytes());
+        if output_len > hash.len() {
+            output_slice[hash.len()] = 0;
+        }
+
+        ok()
+    }
+
+    /// Verifies `password` against a PHC hash string produced by
+    /// [`hash_password`]/[`hash_password_ex`]. The scheme is read off the
+    /// PHC string itself (`$argon2id$...` vs
+    /// [`crate::kdf::verify_password_pbkdf2`]'s `$pbkdf2-sha512$...`)
+    /// rather than taking a `scheme` parameter, since a PHC string already
+    /// says which scheme produced it. Returns
+    /// [`CryptoErrorCode::AuthenticationFailed`] on any mismatch or
+    /// malformed hash string.
+    ///
+    /// # Safety
+    ///
+    /// `password_ptr` must point to a valid buffer of at least
+    /// `password_len` bytes; `hash_ptr` to a valid buffer of at least
+    /// `hash_len` bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn verify_password(
+        password_ptr: *const u8, password_len: usize,
+        hash_ptr: *const u8, hash_len: usize
+    ) -> i32 {
+        if password_ptr.is_null() || hash_ptr.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let password = std::slice::from_raw_parts(password_ptr, password_len);
+        let hash_bytes = std::slice::from_raw_parts(hash_ptr, hash_len);
+        let Ok(hash_str) = std::str::from_utf8(hash_bytes) else {
+            return fail(CryptoErrorCode::AuthenticationFailed);
+        };
+
+        let matched = if hash_str.starts_with("$pbkdf2-sha512$") {
+            crate::kdf::verify_password_pbkdf2(password, hash_str)
+        } else {
+            use argon2::password_hash::{PasswordHash, PasswordVerifier};
+            match PasswordHash::new(hash_str) {
+                Ok(parsed) => Argon2::default().verify_password(password, &parsed).is_ok(),
+                Err(_) => false,
+            }
+        };
+
+        if matched {
+            ok()
+        } else {
+            fail(CryptoErrorCode::AuthenticationFailed)
+        }
+    }
+
+    /// Derives a key from a password with a caller-selected [`KdfScheme`].
+    /// For [`crate::KdfScheme::Pbkdf2HmacSha512`], `iterations` selects the
+    /// PBKDF2 round count; it is ignored for Argon2id.
+    ///
+    /// # Safety
+    ///
+    /// Same pointer/length requirements as [`derive_key_from_password`].
+    #[no_mangle]
+    pub unsafe extern "C" fn derive_key_from_password_ex(
+        password_ptr: *const u8, password_len: usize,
+        salt_ptr: *const u8, salt_len: usize,
+        scheme: u8, iterations: u32,
+        key_ptr: *mut u8, key_len: usize
+    ) -> i32 {
+        if password_ptr.is_null() || salt_ptr.is_null() || key_ptr.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let Some(scheme) = crate::KdfScheme::from_u8(scheme) else {
+            return fail(CryptoErrorCode::UnsupportedScheme);
+        };
+
+        let password = std::slice::from_raw_parts(password_ptr, password_len);
+        let salt = std::slice::from_raw_parts(salt_ptr, salt_len);
+        let key_slice = std::slice::from_raw_parts_mut(key_ptr, key_len);
+
+        match scheme {
+            crate::KdfScheme::Argon2id => {
+                if key_len != 32 {
+                    return fail(CryptoErrorCode::InvalidParams);
+                }
+                let mut key = [0u8; 32];
+                if Argon2::default().hash_password_into(password, salt, &mut key).is_err() {
+                    return fail(CryptoErrorCode::KeyDerivationError);
+                }
+                key_slice.copy_from_slice(&key);
+                key.zeroize();
+            }
+            crate::KdfScheme::Pbkdf2HmacSha512 => {
+                let iterations = if iterations == 0 { crate::kdf::DEFAULT_PBKDF2_ITERATIONS } else { iterations };
+                crate::kdf::derive_key_pbkdf2(password, salt, iterations, key_slice);
+            }
+        }
+
+        ok()
+    }
+
+    /// Derives a 32-byte Argon2id key from `password` and `salt` with
+    /// caller-tuned cost, instead of `Argon2::default()`. Rejects
+    /// `parallelism == 0` and memory below
+    /// [`crate::kdf::Argon2Params::MIN_MEMORY_KIB`] with `InvalidParams`.
+    ///
+    /// # Safety
+    ///
+    /// Same pointer/length requirements as [`derive_key_from_password`].
+    /// `params` must point to a valid [`crate::kdf::Argon2Params`].
+    #[no_mangle]
+    pub unsafe extern "C" fn derive_key_from_password_with_params(
+        password_ptr: *const u8, password_len: usize,
+        salt_ptr: *const u8, salt_len: usize,
+        params: *const crate::kdf::Argon2Params,
+        key_ptr: *mut u8, key_len: usize
+    ) -> i32 {
+        if password_ptr.is_null() || salt_ptr.is_null() || params.is_null() || key_ptr.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        if key_len != 32 {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let password = std::slice::from_raw_parts(password_ptr, password_len);
+        let salt = std::slice::from_raw_parts(salt_ptr, salt_len);
+        let key_slice = std::slice::from_raw_parts_mut(key_ptr, key_len);
+
+        match derive_key_from_password_internal_with_params(password, salt, *params) {
+            Ok(mut key) => {
+                key_slice.copy_from_slice(&key);
+                key.zeroize();
+                ok()
+            }
+            Err("parallelism must be nonzero") | Err("memory_kib below the minimum recommended cost") => {
+                fail(CryptoErrorCode::InvalidParams)
+            }
+            Err(_) => fail(CryptoErrorCode::KeyDerivationError),
+        }
+    }
+
+    /// Starts the UKEY2-style handshake as the initiator. Writes the
+    /// `ClientInit` message to `message_out` and hands back an opaque
+    /// handshake-state handle in `*state_out` that must be passed to a
+    /// later `handshake_finish` call.
+    ///
+    /// # Safety
+    ///
+    /// `message_out` must point to a buffer of at least 64 bytes, and
+    /// `state_out` must point to a valid `*mut HandshakeState`.
+    #[no_mangle]
+    pub unsafe extern "C" fn handshake_client_init(
+        message_out: *mut u8, message_out_max_len: usize, message_out_len: *mut usize,
+        state_out: *mut *mut crate::handshake::HandshakeState
+    ) -> i32 {
+        if message_out.is_null() || message_out_len.is_null() || state_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let (state, message) = crate::handshake::client_init();
+        if message_out_max_len < message.len() {
+            *message_out_len = message.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+
+        std::slice::from_raw_parts_mut(message_out, message_out_max_len)[..message.len()]
+            .copy_from_slice(&message);
+        *message_out_len = message.len();
+        *state_out = std::boxed::Box::into_raw(std::boxed::Box::new(
+            crate::handshake::HandshakeState::Initiator(state),
+        ));
+
+        ok()
+    }
+
+    /// Accepts a `ClientInit` message as the responder. Writes the
+    /// `ServerInit` message to `message_out` and hands back an opaque
+    /// handshake-state handle in `*state_out`.
+    ///
+    /// # Safety
+    ///
+    /// `client_message_ptr` must point to a valid buffer of
+    /// `client_message_len` bytes; `message_out` and `state_out` follow
+    /// the same requirements as in [`handshake_client_init`].
+    #[no_mangle]
+    pub unsafe extern "C" fn handshake_server_init(
+        client_message_ptr: *const u8, client_message_len: usize,
+        message_out: *mut u8, message_out_max_len: usize, message_out_len: *mut usize,
+        state_out: *mut *mut crate::handshake::HandshakeState
+    ) -> i32 {
+        if client_message_ptr.is_null() || message_out.is_null() || message_out_len.is_null() || state_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let client_message = std::slice::from_raw_parts(client_message_ptr, client_message_len);
+        let (state, message) = match crate::handshake::server_init(client_message) {
+            Ok(result) => result,
+            Err(_) => return fail(CryptoErrorCode::InvalidParams),
+        };
+
+        if message_out_max_len < message.len() {
+            *message_out_len = message.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+
+        std::slice::from_raw_parts_mut(message_out, message_out_max_len)[..message.len()]
+            .copy_from_slice(&message);
+        *message_out_len = message.len();
+        *state_out = std::boxed::Box::into_raw(std::boxed::Box::new(
+            crate::handshake::HandshakeState::Responder(state),
+        ));
+
+        ok()
+    }
+
+    /// Finishes a handshake started by either [`handshake_client_init`] or
+    /// [`handshake_server_init`], consuming `state`. On success, writes the
+    /// 32-byte session key to `session_key_out` and the 6-byte
+    /// human-verifiable auth string to `auth_string_out`. For the
+    /// initiator, also writes the `ClientFinish` message to `message_out`
+    /// (the responder passes an empty `message_out` since it has nothing
+    /// left to send). Returns `CryptoErrorCode::AuthenticationFailed` if
+    /// the responder's commitment check fails.
+    ///
+    /// # Safety
+    ///
+    /// `state` must be a handle previously returned by
+    /// `handshake_client_init`/`handshake_server_init` and not already
+    /// consumed; `peer_message_ptr` must point to a valid buffer of
+    /// `peer_message_len` bytes; `session_key_out` must point to a
+    /// 32-byte buffer and `auth_string_out` to a 6-byte buffer.
+    #[no_mangle]
+    pub unsafe extern "C" fn handshake_finish(
+        state: *mut crate::handshake::HandshakeState,
+        peer_message_ptr: *const u8, peer_message_len: usize,
+        message_out: *mut u8, message_out_max_len: usize, message_out_len: *mut usize,
+        session_key_out: *mut u8,
+        auth_string_out: *mut u8
+    ) -> i32 {
+        if state.is_null() || peer_message_ptr.is_null() || session_key_out.is_null() || auth_string_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let state = *std::boxed::Box::from_raw(state);
+        let peer_message = std::slice::from_raw_parts(peer_message_ptr, peer_message_len);
+
+        let (outgoing, auth_string, session_key) = match crate::handshake::finish(state, peer_message) {
+            Ok(result) => result,
+            Err(_) => return fail(CryptoErrorCode::AuthenticationFailed),
+        };
+
+        if let Some(outgoing) = outgoing {
+            if message_out.is_null() || message_out_len.is_null() || message_out_max_len < outgoing.len() {
+                return fail(CryptoErrorCode::BufferTooSmall);
+            }
+            std::slice::from_raw_parts_mut(message_out, message_out_max_len)[..outgoing.len()]
+                .copy_from_slice(&outgoing);
+            *message_out_len = outgoing.len();
+        }
+
+        std::slice::from_raw_parts_mut(session_key_out, 32).copy_from_slice(&session_key);
+        std::slice::from_raw_parts_mut(auth_string_out, auth_string.len()).copy_from_slice(&auth_string);
+
+        ok()
+    }
+
+    /// Splits `secret` into `total_shares` Shamir shares (see the
+    /// `sharing` module), any `threshold` of which reconstruct it via
+    /// [`combine_shares`]. Writes `total_shares` shares into `shares_out`
+    /// back-to-back, each `share_stride` bytes: the x-coordinate byte
+    /// followed by `secret_len` evaluated bytes. `share_stride` must
+    /// equal `secret_len + 1`.
+    ///
+    /// # Safety
+    ///
+    /// `secret_ptr` must point to a valid buffer of at least `secret_len`
+    /// bytes, and `shares_out` to a buffer of at least
+    /// `share_stride * total_shares` bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn split_secret(
+        secret_ptr: *const u8, secret_len: usize,
+        threshold: u8, total_shares: u8,
+        shares_out: *mut u8, share_stride: usize
+    ) -> i32 {
+        if secret_ptr.is_null() || shares_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        if share_stride != secret_len + 1 {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let secret = std::slice::from_raw_parts(secret_ptr, secret_len);
+
+        let shares = match crate::sharing::split_secret(secret, threshold, total_shares) {
+            Ok(shares) => shares,
+            Err(_) => return fail(CryptoErrorCode::InvalidParams),
+        };
+
+        let out = std::slice::from_raw_parts_mut(shares_out, share_stride * total_shares as usize);
+        for (i, share) in shares.iter().enumerate() {
+            let start = i * share_stride;
+            out[start] = share.x;
+            out[start + 1..start + share_stride].copy_from_slice(&share.y);
+        }
+
+        ok()
+    }
+
+    /// Reconstructs a secret from `share_count` Shamir shares (see
+    /// [`split_secret`]), each `share_len` bytes (`1 + secret_len`: the
+    /// x-coordinate followed by the evaluated bytes), packed back-to-back
+    /// at `shares_ptr`. Writes the `share_len - 1`-byte secret to
+    /// `output_ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `shares_ptr` must point to a valid buffer of at least
+    /// `share_count * share_len` bytes, and `output_ptr` to a buffer of
+    /// at least `output_max_len` bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn combine_shares(
+        shares_ptr: *const u8, share_count: usize, share_len: usize,
+        output_ptr: *mut u8, output_max_len: usize, output_len: *mut usize
+    ) -> i32 {
+        if shares_ptr.is_null() || output_ptr.is_null() || output_len.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        if share_len < 2 {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let raw = std::slice::from_raw_parts(shares_ptr, share_count * share_len);
+        let secret_len = share_len - 1;
+        let shares: std::vec::Vec<crate::sharing::Share> = (0..share_count)
+            .map(|i| {
+                let start = i * share_len;
+                crate::sharing::Share {
+                    x: raw[start],
+                    y: raw[start + 1..start + share_len].to_vec(),
+                }
+            })
+            .collect();
+
+        let secret = match crate::sharing::combine_shares(&shares) {
+            Ok(secret) => secret,
+            Err(_) => return fail(CryptoErrorCode::InvalidParams),
+        };
+
+        if output_max_len < secret_len {
+            *output_len = secret_len;
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+
+        let output_slice = std::slice::from_raw_parts_mut(output_ptr, output_max_len);
+        output_slice[..secret_len].copy_from_slice(&secret);
+        *output_len = secret_len;
+
+        ok()
+    }
+
+    /// Generates a fresh X25519 keypair for [`encrypt_asymmetric`]/
+    /// [`decrypt_asymmetric`]: writes the 32-byte public key to `pub_out`
+    /// and the 32-byte private key to `priv_out`.
+    ///
+    /// # Safety
+    ///
+    /// `pub_out` and `priv_out` must each point to a valid 32-byte buffer.
+    #[no_mangle]
+    pub unsafe extern "C" fn generate_keypair(pub_out: *mut u8, priv_out: *mut u8) -> i32 {
+        if pub_out.is_null() || priv_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let (public, private) = crate::asymmetric::generate_keypair();
+        std::slice::from_raw_parts_mut(pub_out, 32).copy_from_slice(&public);
+        std::slice::from_raw_parts_mut(priv_out, 32).copy_from_slice(&private);
+
+        ok()
+    }
+
+    /// Encrypts `data` to the holder of `recipient_pubkey` (see
+    /// [`generate_keypair`]) via ephemeral X25519 ECDH. Unlike
+    /// `encrypt_data`, the caller never needs a shared password.
+    ///
+    /// # Safety
+    ///
+    /// `recipient_pubkey` must point to a valid 32-byte buffer. Other
+    /// pointer/length requirements match [`encrypt_data`].
+    #[no_mangle]
+    pub unsafe extern "C" fn encrypt_asymmetric(
+        data_ptr: *const u8, data_len: usize,
+        recipient_pubkey: *const u8,
+        output_ptr: *mut u8, output_max_len: usize,
+        output_len: *mut usize
+    ) -> i32 {
+        if data_ptr.is_null() || recipient_pubkey.is_null() || output_ptr.is_null() || output_len.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let data = std::slice::from_raw_parts(data_ptr, data_len);
+        let recipient_public: [u8; 32] = std::slice::from_raw_parts(recipient_pubkey, 32).try_into().unwrap();
+
+        let container = match crate::asymmetric::encrypt_asymmetric(data, &recipient_public) {
+            Ok(c) => c,
+            Err(_) => return fail(CryptoErrorCode::EncryptionError),
+        };
+
+        if output_max_len < container.len() {
+            *output_len = container.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+
+        let output_slice = std::slice::from_raw_parts_mut(output_ptr, output_max_len);
+        output_slice[..container.len()].copy_from_slice(&container);
+        *output_len = container.len();
+
+        ok()
+    }
+
+    /// Reverses [`encrypt_asymmetric`] using the recipient's long-term
+    /// private key.
+    ///
+    /// # Safety
+    ///
+    /// `recipient_privkey` must point to a valid 32-byte buffer. Other
+    /// pointer/length requirements match [`decrypt_data`].
+    #[no_mangle]
+    pub unsafe extern "C" fn decrypt_asymmetric(
+        data_ptr: *const u8, data_len: usize,
+        recipient_privkey: *const u8,
+        output_ptr: *mut u8, output_max_len: usize,
+        output_len: *mut usize
+    ) -> i32 {
+        if data_ptr.is_null() || recipient_privkey.is_null() || output_ptr.is_null() || output_len.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let data = std::slice::from_raw_parts(data_ptr, data_len);
+        let recipient_private: [u8; 32] = std::slice::from_raw_parts(recipient_privkey, 32).try_into().unwrap();
+
+        let plaintext = match crate::asymmetric::decrypt_asymmetric(data, &recipient_private) {
+            Ok(p) => p,
+            Err("unsupported container format version") => return fail(CryptoErrorCode::UnsupportedVersion),
+            Err(_) => return fail(CryptoErrorCode::AuthenticationFailed),
+        };
+
+        if output_max_len < plaintext.len() {
+            *output_len = plaintext.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+
+        let output_slice = std::slice::from_raw_parts_mut(output_ptr, output_max_len);
+        output_slice[..plaintext.len()].copy_from_slice(&plaintext);
+        *output_len = plaintext.len();
+
+        ok()
+    }
+
+    /// Splits a fresh FROST signing key into `total_shares` shares (see
+    /// the `frost` module), any `threshold` of which can sign via
+    /// [`frost_sign`]/[`frost_aggregate`]. Writes the 32-byte compressed
+    /// group public key to `group_public_out`, and `total_shares` key
+    /// shares into `shares_out` back-to-back, each `share_stride` bytes:
+    /// `index(1) || threshold(1) || secret_share(32) ||
+    /// verification_share(32)`. `share_stride` must be 66.
+    ///
+    /// # Safety
+    ///
+    /// `group_public_out` must point to a valid 32-byte buffer, and
+    /// `shares_out` to a buffer of at least `share_stride * total_shares`
+    /// bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn frost_keygen(
+        threshold: u8, total_shares: u8,
+        group_public_out: *mut u8,
+        shares_out: *mut u8, share_stride: usize
+    ) -> i32 {
+        if group_public_out.is_null() || shares_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        if share_stride != 66 {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let (group_public, shares) = match crate::frost::keygen(threshold, total_shares) {
+            Ok(result) => result,
+            Err(_) => return fail(CryptoErrorCode::InvalidParams),
+        };
+
+        let group_public_bytes = match crate::frost::compress_point(&group_public) {
+            Ok(bytes) => bytes,
+            Err(e) => return frost_point_error(e),
+        };
+        std::slice::from_raw_parts_mut(group_public_out, 32).copy_from_slice(&group_public_bytes);
+
+        let out = std::slice::from_raw_parts_mut(shares_out, share_stride * total_shares as usize);
+        for (i, share) in shares.iter().enumerate() {
+            let verification_share_bytes = match crate::frost::compress_point(&share.verification_share) {
+                Ok(bytes) => bytes,
+                Err(e) => return frost_point_error(e),
+            };
+            let start = i * share_stride;
+            out[start] = share.index;
+            out[start + 1] = share.threshold;
+            out[start + 2..start + 34].copy_from_slice(share.secret_share.as_bytes());
+            out[start + 34..start + 66].copy_from_slice(&verification_share_bytes);
+        }
+
+        ok()
+    }
+
+    /// Round 1 of FROST signing: draws fresh hiding/binding nonces for
+    /// signer `index` and writes the 64-byte nonce pair (`hiding(32) ||
+    /// binding(32)`) to `nonces_out` and the 65-byte commitment
+    /// (`index(1) || hiding(32) || binding(32)`) to `commitment_out`. The
+    /// nonces must be kept secret and passed to exactly one [`frost_sign`]
+    /// call; the commitment is published to the other signers.
+    ///
+    /// # Safety
+    ///
+    /// `nonces_out` must point to a valid 64-byte buffer, and
+    /// `commitment_out` to a valid 65-byte buffer.
+    #[no_mangle]
+    pub unsafe extern "C" fn frost_commit(index: u8, nonces_out: *mut u8, commitment_out: *mut u8) -> i32 {
+        if nonces_out.is_null() || commitment_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
         }
-        
-        // Convert raw pointers to slices
-        let password = std::slice::from_raw_parts(password_ptr, password_len);
-        let salt = std::slice::from_raw_parts(salt_ptr, salt_len);
-        
-        // Create Argon2id instance
-        let argon2 = Argon2::default();
-        
-        // Derive key
-        let mut key = [0u8; 32];
-        if let Err(_) = argon2.hash_password_into(password, salt, &mut key) {
-            return CryptoErrorCode::KeyDerivationError as i32;
+
+        let (nonces, commitment) = crate::frost::commit(index);
+
+        let hiding_bytes = match crate::frost::compress_point(&commitment.hiding) {
+            Ok(bytes) => bytes,
+            Err(e) => return frost_point_error(e),
+        };
+        let binding_bytes = match crate::frost::compress_point(&commitment.binding) {
+            Ok(bytes) => bytes,
+            Err(e) => return frost_point_error(e),
+        };
+
+        let nonces_slice = std::slice::from_raw_parts_mut(nonces_out, 64);
+        nonces_slice[0..32].copy_from_slice(nonces.hiding.as_bytes());
+        nonces_slice[32..64].copy_from_slice(nonces.binding.as_bytes());
+
+        let commitment_slice = std::slice::from_raw_parts_mut(commitment_out, 65);
+        commitment_slice[0] = commitment.index;
+        commitment_slice[1..33].copy_from_slice(&hiding_bytes);
+        commitment_slice[33..65].copy_from_slice(&binding_bytes);
+
+        ok()
+    }
+
+    /// Maps a [`crate::frost::decompress_point`]/[`crate::frost::compress_point`]
+    /// error to the `CryptoErrorCode` an FFI entry point should `fail()`
+    /// with, distinguishing the identity-element rejection from any other
+    /// malformed-encoding error.
+    fn frost_point_error(err: &str) -> i32 {
+        if err == "identity element is not a valid point encoding"
+            || err == "refusing to serialize the identity element" {
+            fail(CryptoErrorCode::InvalidIdentityElement)
+        } else {
+            fail(CryptoErrorCode::MalformedCommitment)
         }
-        
-        // Write key to output
-        let key_slice = std::slice::from_raw_parts_mut(key_ptr, key_len);
-        key_slice.copy_from_slice(&key);
-        
-        CryptoErrorCode::Success as i32
     }
 
-    // Internal function to derive a key from a password
-    pub(crate) fn derive_key_from_password_internal(password: &[u8]) -> Result<[u8; 32], ()> {
-        // Generate a salt
-        let salt = SaltString::generate(&mut Argon2OsRng);
-        
-        // Create Argon2id instance
-        let argon2 = Argon2::default();
-        
-        // Derive key
+    /// Decodes a 66-byte FROST key share (see [`frost_keygen`]:
+    /// `index(1) || threshold(1) || secret_share(32) ||
+    /// verification_share(32)`) at `ptr`.
+    unsafe fn decode_key_share(ptr: *const u8) -> Result<crate::frost::KeyShare, i32> {
+        let raw = std::slice::from_raw_parts(ptr, 66);
+        let secret_share =
+            crate::frost::decode_scalar(raw[2..34].try_into().unwrap()).map_err(|_| fail(CryptoErrorCode::InvalidShare))?;
+        let verification_share =
+            crate::frost::decompress_point(raw[34..66].try_into().unwrap()).map_err(frost_point_error)?;
+        Ok(crate::frost::KeyShare { index: raw[0], threshold: raw[1], secret_share, verification_share })
+    }
+
+    /// Decodes `count` back-to-back 65-byte FROST commitments (see
+    /// [`frost_commit`]) at `ptr` into `frost::SigningCommitment`s.
+    unsafe fn decode_commitments(ptr: *const u8, count: usize) -> Result<std::vec::Vec<crate::frost::SigningCommitment>, i32> {
+        let raw = std::slice::from_raw_parts(ptr, count * 65);
+        let mut commitments = std::vec::Vec::with_capacity(count);
+        for i in 0..count {
+            let start = i * 65;
+            let hiding = crate::frost::decompress_point(raw[start + 1..start + 33].try_into().unwrap())
+                .map_err(|e| frost_point_error(e))?;
+            let binding = crate::frost::decompress_point(raw[start + 33..start + 65].try_into().unwrap())
+                .map_err(|e| frost_point_error(e))?;
+            commitments.push(crate::frost::SigningCommitment { index: raw[start], hiding, binding });
+        }
+        Ok(commitments)
+    }
+
+    /// Round 2 of FROST signing: computes this signer's signature share
+    /// given their 66-byte key share (see [`frost_keygen`]), their
+    /// 64-byte nonce pair (see [`frost_commit`]), the message, the
+    /// 32-byte compressed group public key, and `commitment_count`
+    /// back-to-back 65-byte commitments from every participating signer
+    /// (including this one). Writes the 32-byte signature share to
+    /// `share_out`.
+    ///
+    /// # Safety
+    ///
+    /// `key_share_ptr` must point to a valid 66-byte buffer,
+    /// `nonces_ptr` to a valid 64-byte buffer, `message_ptr` to a valid
+    /// buffer of `message_len` bytes, `group_public_ptr` to a valid
+    /// 32-byte buffer, `commitments_ptr` to a valid buffer of at least
+    /// `commitment_count * 65` bytes, and `share_out` to a valid 32-byte
+    /// buffer.
+    #[no_mangle]
+    pub unsafe extern "C" fn frost_sign(
+        key_share_ptr: *const u8,
+        nonces_ptr: *const u8,
+        message_ptr: *const u8, message_len: usize,
+        group_public_ptr: *const u8,
+        commitments_ptr: *const u8, commitment_count: usize,
+        share_out: *mut u8
+    ) -> i32 {
+        if key_share_ptr.is_null() || nonces_ptr.is_null() || message_ptr.is_null()
+            || group_public_ptr.is_null() || commitments_ptr.is_null() || share_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let key_share = match decode_key_share(key_share_ptr) {
+            Ok(k) => k,
+            Err(code) => return code,
+        };
+
+        let nonces_raw = std::slice::from_raw_parts(nonces_ptr, 64);
+        let hiding = match crate::frost::decode_scalar(nonces_raw[0..32].try_into().unwrap()) {
+            Ok(s) => s,
+            Err(_) => return fail(CryptoErrorCode::InvalidShare),
+        };
+        let binding = match crate::frost::decode_scalar(nonces_raw[32..64].try_into().unwrap()) {
+            Ok(s) => s,
+            Err(_) => return fail(CryptoErrorCode::InvalidShare),
+        };
+        let nonces = crate::frost::SigningNonces { hiding, binding };
+
+        let message = std::slice::from_raw_parts(message_ptr, message_len);
+        let group_public_bytes: [u8; 32] = std::slice::from_raw_parts(group_public_ptr, 32).try_into().unwrap();
+        let group_public = match crate::frost::decompress_point(&group_public_bytes) {
+            Ok(p) => p,
+            Err(e) => return frost_point_error(e),
+        };
+        let commitments = match decode_commitments(commitments_ptr, commitment_count) {
+            Ok(c) => c,
+            Err(code) => return code,
+        };
+
+        let share = match crate::frost::sign(&key_share, &nonces, message, &group_public, &commitments) {
+            Ok(z) => z,
+            Err(_) => return fail(CryptoErrorCode::InvalidShare),
+        };
+
+        std::slice::from_raw_parts_mut(share_out, 32).copy_from_slice(share.as_bytes());
+
+        ok()
+    }
+
+    /// Aggregates `share_count` signature shares (each a 33-byte
+    /// `index(1) || z_i(32)` pair from [`frost_sign`]) into the final
+    /// signature, given the message, the 32-byte compressed group public
+    /// key, the key's `threshold`, and `commitment_count` back-to-back
+    /// 65-byte commitments (see [`frost_sign`]). Writes the 64-byte
+    /// signature (`r_compressed(32) || z(32)`) to `signature_out`.
+    ///
+    /// # Safety
+    ///
+    /// `message_ptr` must point to a valid buffer of `message_len` bytes,
+    /// `group_public_ptr` to a valid 32-byte buffer, `commitments_ptr` to
+    /// a valid buffer of at least `commitment_count * 65` bytes,
+    /// `shares_ptr` to a valid buffer of at least `share_count * 33`
+    /// bytes, and `signature_out` to a valid 64-byte buffer.
+    #[no_mangle]
+    pub unsafe extern "C" fn frost_aggregate(
+        message_ptr: *const u8, message_len: usize,
+        group_public_ptr: *const u8,
+        threshold: u8,
+        commitments_ptr: *const u8, commitment_count: usize,
+        shares_ptr: *const u8, share_count: usize,
+        signature_out: *mut u8
+    ) -> i32 {
+        if message_ptr.is_null() || group_public_ptr.is_null() || commitments_ptr.is_null()
+            || shares_ptr.is_null() || signature_out.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let message = std::slice::from_raw_parts(message_ptr, message_len);
+        let group_public_bytes: [u8; 32] = std::slice::from_raw_parts(group_public_ptr, 32).try_into().unwrap();
+        let group_public = match crate::frost::decompress_point(&group_public_bytes) {
+            Ok(p) => p,
+            Err(e) => return frost_point_error(e),
+        };
+        let commitments = match decode_commitments(commitments_ptr, commitment_count) {
+            Ok(c) => c,
+            Err(code) => return code,
+        };
+
+        let shares_raw = std::slice::from_raw_parts(shares_ptr, share_count * 33);
+        let mut signature_shares = std::vec::Vec::with_capacity(share_count);
+        for i in 0..share_count {
+            let start = i * 33;
+            let z = match crate::frost::decode_scalar(shares_raw[start + 1..start + 33].try_into().unwrap()) {
+                Ok(s) => s,
+                Err(_) => return fail(CryptoErrorCode::InvalidShare),
+            };
+            signature_shares.push((shares_raw[start], z));
+        }
+
+        let (r, z) = match crate::frost::aggregate(message, &group_public, threshold, &commitments, &signature_shares) {
+            Ok(sig) => sig,
+            Err("fewer signers than the key's threshold") => return fail(CryptoErrorCode::InsufficientSigners),
+            Err(_) => return fail(CryptoErrorCode::InvalidShare),
+        };
+
+        let r_bytes = match crate::frost::compress_point(&r) {
+            Ok(bytes) => bytes,
+            Err(e) => return frost_point_error(e),
+        };
+
+        let signature_slice = std::slice::from_raw_parts_mut(signature_out, 64);
+        signature_slice[0..32].copy_from_slice(&r_bytes);
+        signature_slice[32..64].copy_from_slice(z.as_bytes());
+
+        ok()
+    }
+
+    /// Verifies a 64-byte FROST signature (`r_compressed(32) || z(32)`,
+    /// see [`frost_aggregate`]) against `message` and the 32-byte
+    /// compressed group public key.
+    ///
+    /// # Safety
+    ///
+    /// `message_ptr` must point to a valid buffer of `message_len` bytes;
+    /// `group_public_ptr` and `signature_ptr` must each point to a valid
+    /// 32-byte and 64-byte buffer respectively.
+    #[no_mangle]
+    pub unsafe extern "C" fn frost_verify(
+        message_ptr: *const u8, message_len: usize,
+        group_public_ptr: *const u8,
+        signature_ptr: *const u8
+    ) -> i32 {
+        if message_ptr.is_null() || group_public_ptr.is_null() || signature_ptr.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let message = std::slice::from_raw_parts(message_ptr, message_len);
+        let group_public_bytes: [u8; 32] = std::slice::from_raw_parts(group_public_ptr, 32).try_into().unwrap();
+        let group_public = match crate::frost::decompress_point(&group_public_bytes) {
+            Ok(p) => p,
+            Err(e) => return frost_point_error(e),
+        };
+
+        let signature_raw = std::slice::from_raw_parts(signature_ptr, 64);
+        let r = match crate::frost::decompress_point(signature_raw[0..32].try_into().unwrap()) {
+            Ok(p) => p,
+            Err(e) => return frost_point_error(e),
+        };
+        let z = match crate::frost::decode_scalar(signature_raw[32..64].try_into().unwrap()) {
+            Ok(s) => s,
+            Err(_) => return fail(CryptoErrorCode::InvalidShare),
+        };
+
+        if crate::frost::verify(message, &group_public, &(r, z)) {
+            ok()
+        } else {
+            fail(CryptoErrorCode::AuthenticationFailed)
+        }
+    }
+
+    /// Issues a `local` PASETO-style token (see the `token` module):
+    /// AES-256-GCM-encrypts `claims_json_ptr` under `key_ptr`, with
+    /// `footer_ptr` authenticated but carried in the clear. Writes the
+    /// `v1.local....` token string to `output_ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `key_ptr` must point to a valid 32-byte buffer; `claims_json_ptr`
+    /// to a valid buffer of `claims_json_len` bytes; `footer_ptr` to a
+    /// valid buffer of `footer_len` bytes (may be null iff `footer_len`
+    /// is 0); `output_ptr` to a buffer of at least `output_max_len`
+    /// bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn token_encrypt_local(
+        key_ptr: *const u8,
+        claims_json_ptr: *const u8, claims_json_len: usize,
+        footer_ptr: *const u8, footer_len: usize,
+        output_ptr: *mut u8, output_max_len: usize, output_len: *mut usize
+    ) -> i32 {
+        if key_ptr.is_null() || claims_json_ptr.is_null() || output_ptr.is_null() || output_len.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        if footer_ptr.is_null() && footer_len != 0 {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let key: &[u8; 32] = match std::slice::from_raw_parts(key_ptr, 32).try_into() {
+            Ok(k) => k,
+            Err(_) => return fail(CryptoErrorCode::InvalidParams),
+        };
+        let claims_json = std::slice::from_raw_parts(claims_json_ptr, claims_json_len);
+        let footer = if footer_len == 0 { &[][..] } else { std::slice::from_raw_parts(footer_ptr, footer_len) };
+
+        let token = match crate::token::encrypt_local(key, claims_json, footer) {
+            Ok(t) => t,
+            Err(_) => return fail(CryptoErrorCode::EncryptionError),
+        };
+
+        if output_max_len < token.len() {
+            *output_len = token.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+        std::slice::from_raw_parts_mut(output_ptr, output_max_len)[..token.len()].copy_from_slice(token.as_bytes());
+        *output_len = token.len();
+
+        ok()
+    }
+
+    /// Reverses [`token_encrypt_local`]: authenticates and decrypts the
+    /// `v1.local....` token at `token_ptr`, writing the claims JSON to
+    /// `claims_out` and the footer (if any) to `footer_out`.
+    ///
+    /// # Safety
+    ///
+    /// `key_ptr` must point to a valid 32-byte buffer; `token_ptr` to a
+    /// valid buffer of `token_len` bytes; `claims_out`/`footer_out` to
+    /// buffers of at least `claims_max_len`/`footer_max_len` bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn token_decrypt_local(
+        key_ptr: *const u8,
+        token_ptr: *const u8, token_len: usize,
+        claims_out: *mut u8, claims_max_len: usize, claims_out_len: *mut usize,
+        footer_out: *mut u8, footer_max_len: usize, footer_out_len: *mut usize
+    ) -> i32 {
+        if key_ptr.is_null() || token_ptr.is_null() || claims_out.is_null() || claims_out_len.is_null()
+            || footer_out.is_null() || footer_out_len.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let key: &[u8; 32] = match std::slice::from_raw_parts(key_ptr, 32).try_into() {
+            Ok(k) => k,
+            Err(_) => return fail(CryptoErrorCode::InvalidParams),
+        };
+        let token = match std::str::from_utf8(std::slice::from_raw_parts(token_ptr, token_len)) {
+            Ok(t) => t,
+            Err(_) => return fail(CryptoErrorCode::InvalidParams),
+        };
+
+        let (claims_json, footer) = match crate::token::decrypt_local(key, token) {
+            Ok(result) => result,
+            Err(_) => return fail(CryptoErrorCode::AuthenticationFailed),
+        };
+
+        if claims_max_len < claims_json.len() {
+            *claims_out_len = claims_json.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+        if footer_max_len < footer.len() {
+            *footer_out_len = footer.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+        std::slice::from_raw_parts_mut(claims_out, claims_max_len)[..claims_json.len()].copy_from_slice(&claims_json);
+        *claims_out_len = claims_json.len();
+        std::slice::from_raw_parts_mut(footer_out, footer_max_len)[..footer.len()].copy_from_slice(&footer);
+        *footer_out_len = footer.len();
+
+        ok()
+    }
+
+    /// Issues a `public` PASETO-style token (see the `token` module):
+    /// signs `claims_json_ptr` with the 66-byte `threshold == 1` FROST
+    /// key share at `key_share_ptr` (see [`frost_keygen`]). Writes the
+    /// `v1.public....` token string to `output_ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `key_share_ptr` must point to a valid 66-byte buffer;
+    /// `group_public_ptr` to a valid 32-byte buffer; `claims_json_ptr` to
+    /// a valid buffer of `claims_json_len` bytes; `footer_ptr` to a valid
+    /// buffer of `footer_len` bytes (may be null iff `footer_len` is 0);
+    /// `output_ptr` to a buffer of at least `output_max_len` bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn token_sign_public(
+        key_share_ptr: *const u8,
+        group_public_ptr: *const u8,
+        claims_json_ptr: *const u8, claims_json_len: usize,
+        footer_ptr: *const u8, footer_len: usize,
+        output_ptr: *mut u8, output_max_len: usize, output_len: *mut usize
+    ) -> i32 {
+        if key_share_ptr.is_null() || group_public_ptr.is_null() || claims_json_ptr.is_null()
+            || output_ptr.is_null() || output_len.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+        if footer_ptr.is_null() && footer_len != 0 {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let key_share = match decode_key_share(key_share_ptr) {
+            Ok(k) => k,
+            Err(code) => return code,
+        };
+        let group_public_bytes: [u8; 32] = std::slice::from_raw_parts(group_public_ptr, 32).try_into().unwrap();
+        let group_public = match crate::frost::decompress_point(&group_public_bytes) {
+            Ok(p) => p,
+            Err(e) => return frost_point_error(e),
+        };
+        let claims_json = std::slice::from_raw_parts(claims_json_ptr, claims_json_len);
+        let footer = if footer_len == 0 { &[][..] } else { std::slice::from_raw_parts(footer_ptr, footer_len) };
+
+        let token = match crate::token::sign_public(&key_share, &group_public, claims_json, footer) {
+            Ok(t) => t,
+            Err("fewer signers than the key's threshold") | Err("fewer signers than the key share's threshold") => {
+                return fail(CryptoErrorCode::InsufficientSigners);
+            }
+            Err("refusing to serialize the identity element") => return fail(CryptoErrorCode::InvalidIdentityElement),
+            Err(_) => return fail(CryptoErrorCode::InvalidShare),
+        };
+
+        if output_max_len < token.len() {
+            *output_len = token.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+        std::slice::from_raw_parts_mut(output_ptr, output_max_len)[..token.len()].copy_from_slice(token.as_bytes());
+        *output_len = token.len();
+
+        ok()
+    }
+
+    /// Reverses [`token_sign_public`]: verifies the `v1.public....` token
+    /// at `token_ptr` against the 32-byte compressed group public key at
+    /// `group_public_ptr`, writing the claims JSON to `claims_out` and
+    /// the footer (if any) to `footer_out`.
+    ///
+    /// # Safety
+    ///
+    /// `group_public_ptr` must point to a valid 32-byte buffer;
+    /// `token_ptr` to a valid buffer of `token_len` bytes;
+    /// `claims_out`/`footer_out` to buffers of at least
+    /// `claims_max_len`/`footer_max_len` bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn token_verify_public(
+        group_public_ptr: *const u8,
+        token_ptr: *const u8, token_len: usize,
+        claims_out: *mut u8, claims_max_len: usize, claims_out_len: *mut usize,
+        footer_out: *mut u8, footer_max_len: usize, footer_out_len: *mut usize
+    ) -> i32 {
+        if group_public_ptr.is_null() || token_ptr.is_null() || claims_out.is_null() || claims_out_len.is_null()
+            || footer_out.is_null() || footer_out_len.is_null() {
+            return fail(CryptoErrorCode::InvalidParams);
+        }
+
+        let group_public_bytes: [u8; 32] = std::slice::from_raw_parts(group_public_ptr, 32).try_into().unwrap();
+        let group_public = match crate::frost::decompress_point(&group_public_bytes) {
+            Ok(p) => p,
+            Err(e) => return frost_point_error(e),
+        };
+        let token = match std::str::from_utf8(std::slice::from_raw_parts(token_ptr, token_len)) {
+            Ok(t) => t,
+            Err(_) => return fail(CryptoErrorCode::InvalidParams),
+        };
+
+        let (claims_json, footer) = match crate::token::verify_public(&group_public, token) {
+            Ok(result) => result,
+            Err(_) => return fail(CryptoErrorCode::AuthenticationFailed),
+        };
+
+        if claims_max_len < claims_json.len() {
+            *claims_out_len = claims_json.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+        if footer_max_len < footer.len() {
+            *footer_out_len = footer.len();
+            return fail(CryptoErrorCode::BufferTooSmall);
+        }
+        std::slice::from_raw_parts_mut(claims_out, claims_max_len)[..claims_json.len()].copy_from_slice(&claims_json);
+        *claims_out_len = claims_json.len();
+        std::slice::from_raw_parts_mut(footer_out, footer_max_len)[..footer.len()].copy_from_slice(&footer);
+        *footer_out_len = footer.len();
+
+        ok()
+    }
+
+    // Internal function to derive a key from a password and an explicit
+    // salt, using `Argon2::default()`'s cost. The salt must be supplied by
+    // the caller (and persisted -- see the container header in the
+    // `container` module) rather than generated here, since a
+    // freshly-generated salt can never be reproduced at decrypt time.
+    pub(crate) fn derive_key_from_password_internal(password: &[u8], salt: &[u8]) -> Result<[u8; 32], ()> {
+        derive_key_from_password_internal_with_params(password, salt, crate::kdf::Argon2Params::default_cost())
+            .map_err(|_| ())
+    }
+
+    /// Like [`derive_key_from_password_internal`], but with tunable
+    /// Argon2 cost (see [`crate::kdf::Argon2Params`]) instead of always
+    /// using `Argon2::default()`.
+    pub(crate) fn derive_key_from_password_internal_with_params(
+        password: &[u8],
+        salt: &[u8],
+        params: crate::kdf::Argon2Params,
+    ) -> Result<[u8; 32], &'static str> {
+        let argon2 = params.build()?;
+
         let mut key = [0u8; 32];
-        argon2.hash_password_into(password, salt.as_str().as_bytes(), &mut key)
-            .map_err(|_| ())?;
-        
+        argon2.hash_password_into(password, salt, &mut key)
+            .map_err(|_| "key derivation failed")?;
+
         Ok(key)
     }
 }
@@ -506,86 +2335,325 @@ mod std_features {
 #[cfg(feature = "embedded")]
 mod embedded_features {
     use super::*;
-    
-    // Simple key derivation for embedded targets
-    // This is a placeholder and should be replaced with a more secure implementation
+    #[cfg(all(not(feature = "std"), feature = "embedded"))]
+    use hkdf::Hkdf;
+    #[cfg(all(not(feature = "std"), feature = "embedded"))]
+    use sha2::Sha256;
+
+    /// Fixed HKDF salt for [`simple_key_derivation`]. A per-device-unique
+    /// salt would be stronger, but there's nowhere to persist one on this
+    /// path yet (no container header on embedded, unlike `std_features`'
+    /// Argon2 salt); a fixed salt still domain-separates this expansion
+    /// from any other HKDF use in the crate.
+    #[cfg(all(not(feature = "std"), feature = "embedded"))]
+    const EMBEDDED_KDF_SALT: &[u8] = b"crusty-core/embedded-kdf/v1";
+
+    /// Abstracts AES-256-GCM over a hardware accelerator, so
+    /// `encrypt_with_hardware`/`decrypt_with_hardware` don't need to know
+    /// which accelerator they're calling. Implementations write into a
+    /// caller-provided buffer and return the number of bytes written,
+    /// matching the fixed-capacity, no-heap-allocation style the rest of
+    /// the embedded path uses. There's no software implementation of this
+    /// trait: `encrypt_data`/`decrypt_data` fall back to a plain
+    /// `Aes256Gcm` call inline when no accelerator is available, since
+    /// that path already owns the `heapless::Vec` buffers this trait's
+    /// signature would just be copying.
+    pub(crate) trait HardwareCrypto {
+        fn encrypt(&mut self, key: &[u8; 32], nonce: &[u8; 12], data: &[u8], out: &mut [u8]) -> Result<usize, CryptoErrorCode>;
+        fn decrypt(&mut self, key: &[u8; 32], nonce: &[u8; 12], ciphertext: &[u8], out: &mut [u8]) -> Result<usize, CryptoErrorCode>;
+    }
+
+    /// STM32H5 AES/HASH peripheral accelerator. Only compiled in for the
+    /// STM32H573I-DK target (see `build.rs`'s `stm32h573i_dk` cfg).
+    #[cfg(stm32h573i_dk)]
+    pub(crate) struct Stm32H5Crypto {
+        initialized: bool,
+    }
+
+    #[cfg(stm32h573i_dk)]
+    impl Stm32H5Crypto {
+        /// Attempts to bring up the AES/HASH peripheral clocks. Returns
+        /// `None` if the peripheral can't be initialized (e.g. the clock
+        /// tree hasn't been configured yet), in which case callers must
+        /// fall back to the inline software AES-GCM path in
+        /// `encrypt_data`/`decrypt_data`.
+        pub(crate) fn new() -> Option<Self> {
+            // Placeholder for the real peripheral bring-up (RCC clock
+            // enable + AES/HASH register init). Replaced with the actual
+            // PAC/HAL calls once the board support crate is wired in.
+            None
+        }
+    }
+
+    #[cfg(stm32h573i_dk)]
+    impl HardwareCrypto for Stm32H5Crypto {
+        fn encrypt(&mut self, _key: &[u8; 32], _nonce: &[u8; 12], _data: &[u8], _out: &mut [u8]) -> Result<usize, CryptoErrorCode> {
+            Err(CryptoErrorCode::HardwareNotAvailable)
+        }
+
+        fn decrypt(&mut self, _key: &[u8; 32], _nonce: &[u8; 12], _ciphertext: &[u8], _out: &mut [u8]) -> Result<usize, CryptoErrorCode> {
+            Err(CryptoErrorCode::HardwareNotAvailable)
+        }
+    }
+
+    /// Key derivation for embedded targets. Argon2id (the std path's
+    /// default) is too memory-hungry for the STM32H5's SRAM budget, so
+    /// this expands the password directly with HKDF-SHA256 instead --
+    /// nowhere near Argon2id's resistance to offline brute force, but a
+    /// fixed, salted, one-way expansion rather than the raw password
+    /// bytes repeated into the key, which is what this replaces.
     pub(crate) fn simple_key_derivation(password: &[u8]) -> Result<[u8; 32], ()> {
+        let hk = Hkdf::<Sha256>::new(Some(EMBEDDED_KDF_SALT), password);
         let mut key = [0u8; 32];
-        
-        // Simple key derivation: repeat the password to fill the key
-        for (i, &byte) in password.iter().cycle().take(32).enumerate() {
-            key[i] = byte;
-        }
-        
+        hk.expand(b"crusty-embedded-key", &mut key).map_err(|_| ())?;
         Ok(key)
     }
-    
-    // Get random bytes using hardware RNG if available
+
+    // Fills `buffer` with random bytes from the STM32H5's hardware RNG
+    // peripheral, falling back to a non-cryptographic PRNG only on targets
+    // that don't have one (e.g. running the embedded feature set under a
+    // host-side test harness).
     pub(crate) fn get_random_bytes(buffer: &mut [u8]) -> Result<(), ()> {
-        #[cfg(feature = "stm32h573i_dk")]
+        #[cfg(stm32h573i_dk)]
         {
-            // Use STM32H5 hardware RNG
-            // This is a placeholder and should be replaced with actual hardware RNG implementation
+            // Safety: the RNG peripheral isn't otherwise owned/shared in
+            // this crate, and enabling its clock is idempotent, so
+            // stealing the peripheral handle here rather than threading
+            // it through every caller is consistent with this module's
+            // existing `Stm32H5Crypto::new()` bring-up.
+            let peripherals = unsafe { stm32h5::Peripherals::steal() };
+            peripherals.RCC.ahb2enr().modify(|_, w| w.rngen().set_bit());
+            peripherals.RNG.cr().modify(|_, w| w.rngen().set_bit());
+
             for byte in buffer.iter_mut() {
-                *byte = 0x42; // Placeholder, replace with actual RNG
+                while !peripherals.RNG.sr().read().drdy().bit_is_set() {
+                    if peripherals.RNG.sr().read().seis().bit_is_set() || peripherals.RNG.sr().read().ceis().bit_is_set() {
+                        // Clear the fault and re-seed by reading DR, per
+                        // the RNG peripheral's error-recovery sequence.
+                        peripherals.RNG.sr().modify(|_, w| w.seis().clear_bit().ceis().clear_bit());
+                    }
+                }
+                *byte = peripherals.RNG.dr().read().rndata().bits() as u8;
             }
             return Ok(());
         }
-        
-        // Fallback to a simple PRNG if hardware RNG is not available
-        // This is not secure and should be replaced with a better solution
-        let seed = 0x12345678;
-        let mut state = seed;
-        
-        for byte in buffer.iter_mut() {
-            state = state.wrapping_mul(1103515245).wrapping_add(12345);
-            *byte = ((state >> 16) & 0xFF) as u8;
+
+        // Fallback PRNG for non-hardware targets; not cryptographically
+        // secure, so it must never be reachable on `stm32h573i_dk`.
+        #[cfg(not(stm32h573i_dk))]
+        {
+            let seed = 0x12345678;
+            let mut state = seed;
+
+            for byte in buffer.iter_mut() {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                *byte = ((state >> 16) & 0xFF) as u8;
+            }
+
+            Ok(())
         }
-        
-        Ok(())
     }
-    
-    // Encrypt data using hardware acceleration if available
+
+    /// Encrypts with the hardware accelerator if one initializes
+    /// successfully, otherwise returns `Ok(HardwareNotAvailable)` (or
+    /// `Err(())` on targets with no accelerator compiled in) so the
+    /// caller can decide whether to fall back to software. Unlike the
+    /// original placeholder, this makes `HardwareNotAvailable` actually
+    /// reachable instead of always falling through silently.
     pub(crate) unsafe fn encrypt_with_hardware(
+        key: &[u8; 32],
+        nonce: &[u8; 12],
         data: &[u8],
-        password: &[u8],
         output_ptr: *mut u8,
         output_max_len: usize,
         output_len: *mut usize
     ) -> Result<i32, ()> {
-        #[cfg(feature = "stm32h573i_dk")]
+        #[cfg(stm32h573i_dk)]
         {
-            // Use STM32H5 hardware crypto accelerator
-            // This is a placeholder and should be replaced with actual hardware implementation
-            
-            // For now, just return an error to fall back to software implementation
-            return Err(());
+            let Some(mut accelerator) = Stm32H5Crypto::new() else {
+                return Ok(CryptoErrorCode::HardwareNotAvailable as i32);
+            };
+            let out = core::slice::from_raw_parts_mut(output_ptr, output_max_len);
+            return match accelerator.encrypt(&(*key), nonce, data, out) {
+                Ok(written) => {
+                    *output_len = written;
+                    Ok(CryptoErrorCode::Success as i32)
+                }
+                Err(code) => Ok(code as i32),
+            };
         }
-        
-        // Hardware acceleration not available
+
+        // No accelerator compiled in for this target.
+        #[allow(unreachable_code)]
         Err(())
     }
-    
-    // Decrypt data using hardware acceleration if available
+
+    /// Decrypts with the hardware accelerator if one initializes
+    /// successfully; see [`encrypt_with_hardware`].
     pub(crate) unsafe fn decrypt_with_hardware(
-        data: &[u8],
-        password: &[u8],
+        key: &[u8; 32],
+        nonce: &[u8; 12],
+        ciphertext: &[u8],
         output_ptr: *mut u8,
         output_max_len: usize,
         output_len: *mut usize
     ) -> Result<i32, ()> {
-        #[cfg(feature = "stm32h573i_dk")]
+        #[cfg(stm32h573i_dk)]
         {
-            // Use STM32H5 hardware crypto accelerator
-            // This is a placeholder and should be replaced with actual hardware implementation
-            
-            // For now, just return an error to fall back to software implementation
-            return Err(());
+            let Some(mut accelerator) = Stm32H5Crypto::new() else {
+                return Ok(CryptoErrorCode::HardwareNotAvailable as i32);
+            };
+            let out = core::slice::from_raw_parts_mut(output_ptr, output_max_len);
+            return match accelerator.decrypt(&(*key), nonce, ciphertext, out) {
+                Ok(written) => {
+                    *output_len = written;
+                    Ok(CryptoErrorCode::Success as i32)
+                }
+                Err(code) => Ok(code as i32),
+            };
         }
-        
-        // Hardware acceleration not available
+
+        // No accelerator compiled in for this target.
+        #[allow(unreachable_code)]
         Err(())
     }
+
+    /// Maximum plaintext bytes per chunk for the embedded streaming API,
+    /// matching the fixed-capacity buffers `encrypt_data`/`decrypt_data`
+    /// already use for the whole message.
+    const STREAM_CHUNK_CAP: usize = 2048;
+    /// `STREAM_CHUNK_CAP` plaintext bytes, plus the leading final-chunk
+    /// flag byte and the trailing 16-byte GCM tag.
+    const STREAM_CHUNK_OUT_CAP: usize = 1 + STREAM_CHUNK_CAP + 16;
+
+    /// Builds the per-chunk nonce: a random 32-bit stream prefix (fixed
+    /// for the life of the stream) followed by a 64-bit big-endian chunk
+    /// counter, the same scheme `streaming::EncryptStream` uses on std.
+    fn stream_chunk_nonce(stream_prefix: [u8; 4], counter: u64) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[0..4].copy_from_slice(&stream_prefix);
+        nonce[4..12].copy_from_slice(&counter.to_be_bytes());
+        nonce
+    }
+
+    /// Streaming AES-256-GCM encryption context for embedded targets. The
+    /// `HardwareCrypto` trait has no associated-data parameter, so unlike
+    /// `streaming::EncryptStream` (which flags the final chunk via AEAD
+    /// AAD), the flag is a plaintext byte prepended to each chunk before
+    /// encryption: `[is_final] || chunk`.
+    pub struct EmbeddedEncryptStream {
+        key: [u8; 32],
+        stream_prefix: [u8; 4],
+        counter: u64,
+        finished: bool,
+    }
+
+    impl Drop for EmbeddedEncryptStream {
+        fn drop(&mut self) {
+            self.key.zeroize();
+        }
+    }
+
+    impl EmbeddedEncryptStream {
+        pub(crate) fn new(key: [u8; 32], stream_prefix: [u8; 4]) -> Self {
+            EmbeddedEncryptStream { key, stream_prefix, counter: 0, finished: false }
+        }
+
+        pub(crate) fn update(&mut self, chunk: &[u8]) -> Result<Vec<u8, STREAM_CHUNK_OUT_CAP>, CryptoErrorCode> {
+            self.seal(chunk, false)
+        }
+
+        pub(crate) fn finish(&mut self, chunk: &[u8]) -> Result<Vec<u8, STREAM_CHUNK_OUT_CAP>, CryptoErrorCode> {
+            self.seal(chunk, true)
+        }
+
+        fn seal(&mut self, chunk: &[u8], is_final: bool) -> Result<Vec<u8, STREAM_CHUNK_OUT_CAP>, CryptoErrorCode> {
+            if self.finished {
+                return Err(CryptoErrorCode::InvalidParams);
+            }
+            if chunk.len() > STREAM_CHUNK_CAP {
+                return Err(CryptoErrorCode::BufferTooSmall);
+            }
+
+            let nonce_bytes = stream_chunk_nonce(self.stream_prefix, self.counter);
+            let nonce = Nonce::from_slice(&nonce_bytes);
+            let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
+
+            // Load the flag byte and plaintext into the buffer *before*
+            // sealing it in place -- the buffer must hold the plaintext
+            // going in, not stay empty with the plaintext passed as AAD.
+            let mut buf: Vec<u8, STREAM_CHUNK_OUT_CAP> = Vec::new();
+            buf.push(if is_final { 1 } else { 0 }).map_err(|_| CryptoErrorCode::BufferTooSmall)?;
+            buf.extend_from_slice(chunk).map_err(|_| CryptoErrorCode::BufferTooSmall)?;
+            let tag = cipher.encrypt_in_place_detached(nonce, b"", &mut buf).map_err(|_| CryptoErrorCode::EncryptionError)?;
+            buf.extend_from_slice(&tag).map_err(|_| CryptoErrorCode::BufferTooSmall)?;
+
+            self.counter = self.counter.checked_add(1).ok_or(CryptoErrorCode::InternalError)?;
+            self.finished = is_final;
+            Ok(buf)
+        }
+    }
+
+    /// Streaming AES-256-GCM decryption context for embedded targets; see
+    /// [`EmbeddedEncryptStream`].
+    pub struct EmbeddedDecryptStream {
+        key: [u8; 32],
+        stream_prefix: [u8; 4],
+        counter: u64,
+        finished: bool,
+    }
+
+    impl Drop for EmbeddedDecryptStream {
+        fn drop(&mut self) {
+            self.key.zeroize();
+        }
+    }
+
+    impl EmbeddedDecryptStream {
+        pub(crate) fn new(key: [u8; 32], stream_prefix: [u8; 4]) -> Self {
+            EmbeddedDecryptStream { key, stream_prefix, counter: 0, finished: false }
+        }
+
+        pub(crate) fn update(&mut self, chunk: &[u8]) -> Result<Vec<u8, STREAM_CHUNK_CAP>, CryptoErrorCode> {
+            self.open(chunk, false)
+        }
+
+        pub(crate) fn finish(&mut self, chunk: &[u8]) -> Result<Vec<u8, STREAM_CHUNK_CAP>, CryptoErrorCode> {
+            self.open(chunk, true)
+        }
+
+        fn open(&mut self, chunk: &[u8], is_final: bool) -> Result<Vec<u8, STREAM_CHUNK_CAP>, CryptoErrorCode> {
+            if self.finished {
+                return Err(CryptoErrorCode::InvalidParams);
+            }
+            if chunk.len() < 1 + 16 {
+                return Err(CryptoErrorCode::InvalidParams);
+            }
+
+            let nonce_bytes = stream_chunk_nonce(self.stream_prefix, self.counter);
+            let nonce = Nonce::from_slice(&nonce_bytes);
+            let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
+
+            let (sealed, tag_bytes) = chunk.split_at(chunk.len() - 16);
+            let mut buf: Vec<u8, { STREAM_CHUNK_CAP + 1 }> = Vec::new();
+            buf.extend_from_slice(sealed).map_err(|_| CryptoErrorCode::BufferTooSmall)?;
+            cipher
+                .decrypt_in_place_detached(nonce, b"", &mut buf, tag_bytes.into())
+                .map_err(|_| CryptoErrorCode::AuthenticationFailed)?;
+
+            let flag = *buf.first().ok_or(CryptoErrorCode::AuthenticationFailed)?;
+            if flag != if is_final { 1 } else { 0 } {
+                return Err(CryptoErrorCode::AuthenticationFailed);
+            }
+
+            let mut out: Vec<u8, STREAM_CHUNK_CAP> = Vec::new();
+            out.extend_from_slice(&buf[1..]).map_err(|_| CryptoErrorCode::BufferTooSmall)?;
+
+            self.counter = self.counter.checked_add(1).ok_or(CryptoErrorCode::InternalError)?;
+            self.finished = is_final;
+            Ok(out)
+        }
+    }
 }
 
 // Re-export functions from the modules
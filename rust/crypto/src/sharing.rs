@@ -0,0 +1,227 @@
+//! Shamir secret sharing over GF(2^8).
+//!
+//! Splits a secret into `total_shares` shares such that any `threshold` of
+//! them reconstruct it, but any `threshold - 1` reveal nothing. Modeled on
+//! devolutions-crypto's `generate_shared_key`/`join_shares`.
+//!
+//! For each secret byte independently, [`split_secret`] builds a random
+//! polynomial of degree `threshold - 1` whose constant term is that byte,
+//! then evaluates it at the distinct nonzero x-coordinates `1..=total_shares`.
+//! Field arithmetic is GF(2^8) with AES's reduction polynomial (0x11b):
+//! addition is XOR, multiplication is carry-less multiply followed by
+//! reduction mod `x^8 + x^4 + x^3 + x + 1`. [`combine_shares`] reverses this
+//! with Lagrange interpolation at x=0 over any `threshold` of the shares.
+//!
+//! The x-coordinates must be unique and nonzero -- a zero x-coordinate
+//! would evaluate to the polynomial's constant term (the secret byte)
+//! directly, and a repeated one gives interpolation a singular system.
+//! Coefficients and reconstructed share bytes are zeroized as soon as
+//! they're no longer needed, since they're as sensitive as the secret
+//! itself.
+
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::RngCore;
+#[cfg(feature = "std")]
+use zeroize::Zeroize;
+
+/// One Shamir share: an x-coordinate and the polynomial evaluated at it,
+/// one byte per secret byte. `y` is zeroized on drop.
+#[cfg(feature = "std")]
+pub struct Share {
+    pub x: u8,
+    pub y: std::vec::Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.y.zeroize();
+    }
+}
+
+/// Multiplies two GF(2^8) elements using AES's reduction polynomial
+/// (0x11b).
+fn gf256_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Inverts a nonzero GF(2^8) element via `a^254 = a^-1`, which holds
+/// because the multiplicative group has order 255.
+fn gf256_inv(a: u8) -> u8 {
+    if a == 0 {
+        return 0;
+    }
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf256_mul(result, base);
+        }
+        base = gf256_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn gf256_div(a: u8, b: u8) -> u8 {
+    gf256_mul(a, gf256_inv(b))
+}
+
+/// Evaluates a polynomial (lowest-degree coefficient first) at `x` using
+/// Horner's method in GF(2^8).
+fn eval_polynomial(coefficients: &[u8], x: u8) -> u8 {
+    let mut result = 0u8;
+    for &coefficient in coefficients.iter().rev() {
+        result = gf256_mul(result, x) ^ coefficient;
+    }
+    result
+}
+
+#[cfg(feature = "std")]
+/// Splits `secret` into `total_shares` shares, any `threshold` of which
+/// reconstruct it via [`combine_shares`]. Randomness for the polynomial
+/// coefficients comes from `OsRng`, never the embedded placeholder PRNG.
+pub fn split_secret(secret: &[u8], threshold: u8, total_shares: u8) -> Result<std::vec::Vec<Share>, &'static str> {
+    if threshold == 0 {
+        return Err("threshold must be nonzero");
+    }
+    if total_shares == 0 {
+        return Err("total_shares must be nonzero");
+    }
+    if threshold > total_shares {
+        return Err("threshold must not exceed total_shares");
+    }
+
+    let degree = (threshold - 1) as usize;
+
+    // One random polynomial per secret byte; byte `i` is the constant
+    // term (the thing being shared), the rest are random.
+    let mut coefficients: std::vec::Vec<std::vec::Vec<u8>> = std::vec::Vec::with_capacity(secret.len());
+    for &byte in secret {
+        let mut coeffs = std::vec![0u8; degree + 1];
+        coeffs[0] = byte;
+        if degree > 0 {
+            OsRng.fill_bytes(&mut coeffs[1..]);
+        }
+        coefficients.push(coeffs);
+    }
+
+    // x-coordinates 1..=total_shares are unique and nonzero by
+    // construction (x = 0 would leak the secret byte directly).
+    let mut shares = std::vec::Vec::with_capacity(total_shares as usize);
+    for x in 1..=total_shares {
+        let y = coefficients.iter().map(|coeffs| eval_polynomial(coeffs, x)).collect();
+        shares.push(Share { x, y });
+    }
+
+    for mut coeffs in coefficients {
+        coeffs.zeroize();
+    }
+
+    Ok(shares)
+}
+
+#[cfg(feature = "std")]
+/// Reconstructs the original secret from `shares` (at least `threshold`
+/// of the shares [`split_secret`] produced) via Lagrange interpolation at
+/// x=0 in GF(2^8).
+pub fn combine_shares(shares: &[Share]) -> Result<std::vec::Vec<u8>, &'static str> {
+    if shares.is_empty() {
+        return Err("no shares provided");
+    }
+
+    let secret_len = shares[0].y.len();
+    if shares.iter().any(|share| share.y.len() != secret_len) {
+        return Err("shares have mismatched lengths");
+    }
+
+    let mut seen_x = std::vec::Vec::with_capacity(shares.len());
+    for share in shares {
+        if share.x == 0 {
+            return Err("share x-coordinate must be nonzero");
+        }
+        if seen_x.contains(&share.x) {
+            return Err("duplicate share x-coordinate");
+        }
+        seen_x.push(share.x);
+    }
+
+    let mut secret = std::vec![0u8; secret_len];
+    for (byte_idx, secret_byte) in secret.iter_mut().enumerate() {
+        *secret_byte = lagrange_interpolate_at_zero(shares, byte_idx);
+    }
+    Ok(secret)
+}
+
+/// Evaluates the Lagrange interpolation polynomial for `shares` at x=0,
+/// for a single secret byte position. At x=0 each basis polynomial's
+/// numerator term `(0 - x_j)` is just `x_j`, since GF(2^8) subtraction is
+/// XOR (so `0 - x_j == x_j`).
+fn lagrange_interpolate_at_zero(shares: &[Share], byte_idx: usize) -> u8 {
+    let mut result = 0u8;
+    for (i, share_i) in shares.iter().enumerate() {
+        let mut numerator = 1u8;
+        let mut denominator = 1u8;
+        for (j, share_j) in shares.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            numerator = gf256_mul(numerator, share_j.x);
+            denominator = gf256_mul(denominator, share_i.x ^ share_j.x);
+        }
+        let basis = gf256_div(numerator, denominator);
+        result ^= gf256_mul(share_i.y[byte_idx], basis);
+    }
+    result
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combines_any_threshold_subset_of_shares() {
+        let secret = b"shamir secret sharing test".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        assert_eq!(combine_shares(&shares[0..3]).unwrap(), secret);
+        assert_eq!(combine_shares(&shares[2..5]).unwrap(), secret);
+    }
+
+    #[test]
+    fn fewer_than_threshold_shares_do_not_reconstruct_the_secret() {
+        let secret = b"shamir secret sharing test".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        assert_ne!(combine_shares(&shares[0..2]).unwrap(), secret);
+    }
+
+    #[test]
+    fn split_secret_rejects_threshold_above_total_shares() {
+        assert_eq!(split_secret(b"secret", 4, 3).unwrap_err(), "threshold must not exceed total_shares");
+    }
+
+    #[test]
+    fn combine_shares_rejects_duplicate_x_coordinate() {
+        let secret = b"shamir secret sharing test".to_vec();
+        let mut shares = split_secret(&secret, 3, 5).unwrap();
+        shares[1].x = shares[0].x;
+
+        assert_eq!(combine_shares(&shares[0..3]).unwrap_err(), "duplicate share x-coordinate");
+    }
+}
@@ -0,0 +1,288 @@
+//! PASETO-style authenticated stateless tokens.
+//!
+//! Ad-hoc "encrypt the claims, prepend a MAC" token formats are easy to
+//! get subtly wrong (algorithm confusion, MAC-then-encrypt padding
+//! oracles, a `kid` header that lets an attacker swap algorithms). This
+//! module follows PASETO's approach instead, layered on primitives this
+//! crate already has: `local` tokens are AES-256-GCM over a JSON claims
+//! payload (the same AEAD `encrypt_data`/`container` use), and `public`
+//! tokens are signed with [`crate::frost`]'s Schnorr-over-Ristretto255
+//! signing (a `threshold`-of-1 [`crate::frost::KeyShare`] degenerates to
+//! ordinary single-key Schnorr, so no new signature scheme is needed).
+//!
+//! Every token is `v1.<purpose>.<base64url payload>[.<base64url footer>]`,
+//! where `purpose` is `local` or `public`. The footer is carried
+//! alongside the token in the clear (useful for a key id) but is bound
+//! into the AEAD associated data / the signed message via a PASETO-style
+//! pre-authentication encoding ([`pae`]) that also includes the
+//! `version.purpose` header, so a `local` token's ciphertext can't be
+//! replayed as a `public` token's claims or vice versa.
+//!
+//! `exp`/`nbf` registered claims (Unix timestamps, seconds) are enforced
+//! by [`decrypt_local`]/[`verify_public`] if present in the claims JSON.
+//! These Rust functions return a distinct error string per failure
+//! (e.g. `"authentication failed"`, `"signature verification failed"`,
+//! `"token has expired"`); the FFI wrappers `token_decrypt_local`/
+//! `token_verify_public` collapse every one of them to
+//! `CryptoErrorCode::AuthenticationFailed`, matching this crate's existing
+//! `decrypt_data`/`decrypt_asymmetric` contract.
+
+#[cfg(feature = "std")]
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+#[cfg(feature = "std")]
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+#[cfg(feature = "std")]
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+#[cfg(feature = "std")]
+use curve25519_dalek::ristretto::RistrettoPoint;
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::RngCore;
+#[cfg(feature = "std")]
+use serde_json::Value;
+
+const HEADER_LOCAL: &str = "v1.local.";
+const HEADER_PUBLIC: &str = "v1.public.";
+
+/// Length in bytes of a [`crate::frost`] Schnorr signature
+/// (`r_compressed(32) || z(32)`), appended to the claims JSON in a
+/// `public` token's payload.
+const SIGNATURE_LEN: usize = 64;
+
+/// PASETO's pre-authentication encoding: unambiguously concatenates
+/// `pieces` as `LE64(len(pieces)) || LE64(len(piece_0)) || piece_0 ||
+/// ...`, so that e.g. binding `(header, footer)` can't be confused with
+/// binding `(header || footer,)` -- the length prefixes fix each piece's
+/// boundary.
+fn pae(pieces: &[&[u8]]) -> std::vec::Vec<u8> {
+    let mut out = std::vec::Vec::new();
+    out.extend_from_slice(&(pieces.len() as u64).to_le_bytes());
+    for piece in pieces {
+        out.extend_from_slice(&(piece.len() as u64).to_le_bytes());
+        out.extend_from_slice(piece);
+    }
+    out
+}
+
+/// Checks `exp`/`nbf`, if present in `claims_json`, against the current
+/// time. Claims without either field never expire and are always valid
+/// from the start, matching PASETO's (and JWT's) "claim absent means
+/// unconstrained" convention.
+fn check_registered_claims(claims_json: &[u8]) -> Result<(), &'static str> {
+    let claims: Value = serde_json::from_slice(claims_json).map_err(|_| "claims payload is not valid JSON")?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|_| "system clock is before the Unix epoch")?
+        .as_secs();
+
+    if let Some(exp) = claims.get("exp").and_then(Value::as_u64) {
+        if now >= exp {
+            return Err("token has expired");
+        }
+    }
+    if let Some(nbf) = claims.get("nbf").and_then(Value::as_u64) {
+        if now < nbf {
+            return Err("token is not yet valid");
+        }
+    }
+    Ok(())
+}
+
+/// Issues a `local` token: AES-256-GCM-encrypts `claims_json` under
+/// `key`, with `footer` carried in the clear alongside the ciphertext
+/// but authenticated (via [`pae`]) together with the `v1.local.` header.
+pub fn encrypt_local(key: &[u8; 32], claims_json: &[u8], footer: &[u8]) -> Result<std::string::String, &'static str> {
+    if serde_json::from_slice::<Value>(claims_json).is_err() {
+        return Err("claims payload is not valid JSON");
+    }
+
+    let mut nonce_bytes = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce_bytes);
+
+    let aad = pae(&[HEADER_LOCAL.as_bytes(), footer]);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), Payload { msg: claims_json, aad: &aad })
+        .map_err(|_| "encryption failed")?;
+
+    let mut payload = std::vec::Vec::with_capacity(12 + ciphertext.len());
+    payload.extend_from_slice(&nonce_bytes);
+    payload.extend_from_slice(&ciphertext);
+
+    Ok(finish_token(HEADER_LOCAL, &payload, footer))
+}
+
+/// Reverses [`encrypt_local`]: authenticates and decrypts `token`,
+/// returning `(claims_json, footer)` once the AEAD tag verifies and any
+/// `exp`/`nbf` registered claim is satisfied.
+pub fn decrypt_local(key: &[u8; 32], token: &str) -> Result<(std::vec::Vec<u8>, std::vec::Vec<u8>), &'static str> {
+    let (payload, footer) = split_token(HEADER_LOCAL, token)?;
+    if payload.len() < 12 {
+        return Err("payload too short to contain a nonce");
+    }
+    let (nonce_bytes, ciphertext) = payload.split_at(12);
+
+    let aad = pae(&[HEADER_LOCAL.as_bytes(), &footer]);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let claims_json = cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), Payload { msg: ciphertext, aad: &aad })
+        .map_err(|_| "authentication failed")?;
+
+    check_registered_claims(&claims_json)?;
+
+    Ok((claims_json, footer))
+}
+
+/// Issues a `public` token: signs `claims_json` (and the `v1.public.`
+/// header and `footer`, via [`pae`]) with `key_share` using
+/// [`crate::frost`]'s single-round-trip Schnorr signing. `key_share` must
+/// have `threshold == 1` -- a genuine M-of-N signing key can't issue a
+/// token alone, and [`crate::frost::sign`] rejects the attempt with
+/// `"fewer signers than the key share's threshold"` rather than silently
+/// issuing a token only one of N required signers approved.
+pub fn sign_public(
+    key_share: &crate::frost::KeyShare,
+    group_public: &RistrettoPoint,
+    claims_json: &[u8],
+    footer: &[u8],
+) -> Result<std::string::String, &'static str> {
+    if serde_json::from_slice::<Value>(claims_json).is_err() {
+        return Err("claims payload is not valid JSON");
+    }
+
+    let message = pae(&[HEADER_PUBLIC.as_bytes(), claims_json, footer]);
+
+    let (nonces, commitment) = crate::frost::commit(key_share.index);
+    let share = crate::frost::sign(key_share, &nonces, &message, group_public, &[commitment])?;
+    let (r, z) =
+        crate::frost::aggregate(&message, group_public, key_share.threshold, &[commitment], &[(key_share.index, share)])?;
+    let r_bytes = crate::frost::compress_point(&r)?;
+
+    let mut payload = std::vec::Vec::with_capacity(claims_json.len() + SIGNATURE_LEN);
+    payload.extend_from_slice(claims_json);
+    payload.extend_from_slice(&r_bytes);
+    payload.extend_from_slice(z.as_bytes());
+
+    Ok(finish_token(HEADER_PUBLIC, &payload, footer))
+}
+
+/// Reverses [`sign_public`]: verifies `token`'s Schnorr signature against
+/// `group_public`, returning `(claims_json, footer)` once it checks out
+/// and any `exp`/`nbf` registered claim is satisfied.
+pub fn verify_public(group_public: &RistrettoPoint, token: &str) -> Result<(std::vec::Vec<u8>, std::vec::Vec<u8>), &'static str> {
+    let (payload, footer) = split_token(HEADER_PUBLIC, token)?;
+    if payload.len() < SIGNATURE_LEN {
+        return Err("payload too short to contain a signature");
+    }
+    let (claims_json, signature_bytes) = payload.split_at(payload.len() - SIGNATURE_LEN);
+
+    let r = crate::frost::decompress_point(signature_bytes[0..32].try_into().unwrap())?;
+    let z = crate::frost::decode_scalar(signature_bytes[32..64].try_into().unwrap())?;
+
+    let message = pae(&[HEADER_PUBLIC.as_bytes(), claims_json, &footer]);
+    if !crate::frost::verify(&message, group_public, &(r, z)) {
+        return Err("signature verification failed");
+    }
+
+    check_registered_claims(claims_json)?;
+
+    Ok((claims_json.to_vec(), footer))
+}
+
+/// Assembles `header` and a base64url-encoded `payload`/`footer` into the
+/// final dot-separated token string; the footer segment is omitted
+/// entirely when `footer` is empty, matching PASETO.
+fn finish_token(header: &str, payload: &[u8], footer: &[u8]) -> std::string::String {
+    let mut token = std::format!("{}{}", header, URL_SAFE_NO_PAD.encode(payload));
+    if !footer.is_empty() {
+        token.push('.');
+        token.push_str(&URL_SAFE_NO_PAD.encode(footer));
+    }
+    token
+}
+
+/// Strips `header` from `token` and base64url-decodes the payload and
+/// (if present) footer segments that follow it.
+fn split_token(header: &str, token: &str) -> Result<(std::vec::Vec<u8>, std::vec::Vec<u8>), &'static str> {
+    let rest = token.strip_prefix(header).ok_or("token version/purpose header does not match")?;
+    let mut segments = rest.splitn(2, '.');
+    let payload_b64 = segments.next().ok_or("missing payload segment")?;
+    let footer_b64 = segments.next().unwrap_or("");
+
+    let payload = URL_SAFE_NO_PAD.decode(payload_b64).map_err(|_| "malformed payload encoding")?;
+    let footer = if footer_b64.is_empty() {
+        std::vec::Vec::new()
+    } else {
+        URL_SAFE_NO_PAD.decode(footer_b64).map_err(|_| "malformed footer encoding")?
+    };
+
+    Ok((payload, footer))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_token_round_trip() {
+        let key = [7u8; 32];
+        let claims = br#"{"sub":"alice"}"#;
+        let footer = b"key-id:1";
+
+        let token = encrypt_local(&key, claims, footer).unwrap();
+        let (decrypted_claims, decrypted_footer) = decrypt_local(&key, &token).unwrap();
+
+        assert_eq!(decrypted_claims, claims);
+        assert_eq!(decrypted_footer, footer);
+    }
+
+    #[test]
+    fn local_token_rejects_the_wrong_key() {
+        let key = [7u8; 32];
+        let wrong_key = [9u8; 32];
+        let token = encrypt_local(&key, br#"{"sub":"alice"}"#, b"").unwrap();
+
+        assert_eq!(decrypt_local(&wrong_key, &token), Err("authentication failed"));
+    }
+
+    #[test]
+    fn local_token_rejects_an_expired_claim() {
+        let key = [7u8; 32];
+        let token = encrypt_local(&key, br#"{"exp":1}"#, b"").unwrap();
+
+        assert_eq!(decrypt_local(&key, &token), Err("token has expired"));
+    }
+
+    #[test]
+    fn public_token_round_trip() {
+        let (group_public, mut shares) = crate::frost::keygen(1, 1).unwrap();
+        let key_share = shares.remove(0);
+        let claims = br#"{"sub":"alice"}"#;
+        let footer = b"key-id:1";
+
+        let token = sign_public(&key_share, &group_public, claims, footer).unwrap();
+        let (verified_claims, verified_footer) = verify_public(&group_public, &token).unwrap();
+
+        assert_eq!(verified_claims, claims);
+        assert_eq!(verified_footer, footer);
+    }
+
+    #[test]
+    fn public_token_rejects_a_tampered_payload() {
+        let (group_public, mut shares) = crate::frost::keygen(1, 1).unwrap();
+        let key_share = shares.remove(0);
+        let token = sign_public(&key_share, &group_public, br#"{"sub":"alice"}"#, b"").unwrap();
+
+        // Flip a byte inside the (unencrypted) claims JSON, not the
+        // trailing signature -- this always lands on "signature
+        // verification failed" rather than risking an unrelated "not a
+        // canonical scalar" error if the flip happened to land in `z`.
+        let (payload, footer) = split_token(HEADER_PUBLIC, &token).unwrap();
+        let mut tampered_payload = payload.clone();
+        tampered_payload[0] ^= 0xff;
+        let tampered = finish_token(HEADER_PUBLIC, &tampered_payload, &footer);
+
+        assert_eq!(verify_public(&group_public, &tampered), Err("signature verification failed"));
+    }
+}